@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use nanoserde::DeJson;
+use super::resource_manager::ResourceManager;
+
+const ACHIEVEMENTS_FILE: &str = "achievements.dat";
+/// Stats kept across runs and saved with the unlocks
+const LIFETIME_STATS: &[&str] = &["kills", "big_kills", "shots", "runs"];
+/// Stats that start over with every run
+const RUN_STATS: &[&str] = &["score", "multiplier", "seconds_since_shot"];
+
+#[derive(Clone, Debug, DeJson)]
+pub struct Requirement {
+    pub stat: String,
+    pub at_least: f32,
+}
+
+/// Unlocks once all of its requirements hold at the same time. The title
+/// and description are the `achievement.<id>` strings.
+#[derive(Clone, Debug, DeJson)]
+pub struct AchievementDefinition {
+    pub id: String,
+    pub requirements: Vec<Requirement>,
+}
+
+#[derive(DeJson)]
+struct AchievementsFile {
+    achievements: Vec<AchievementDefinition>,
+}
+
+impl AchievementDefinition {
+    pub fn list_from_json(json: &str) -> Result<Vec<Self>, String> {
+        let file = AchievementsFile::deserialize_json(json).map_err(|err| err.to_string())?;
+        for requirement in file.achievements.iter().flat_map(|achievement| &achievement.requirements) {
+            if !LIFETIME_STATS.contains(&requirement.stat.as_str()) && !RUN_STATS.contains(&requirement.stat.as_str()) {
+                return Err(format!("Unknown stat {}", requirement.stat));
+            }
+        }
+        Ok(file.achievements)
+    }
+}
+
+/// What happened in the game, as far as achievements are concerned.
+#[derive(Clone, Copy, Debug)]
+pub enum GameEvent {
+    RunStarted,
+    Shot,
+    Kill { big: bool },
+    /// Score of the run so far
+    Scored(u32),
+    /// Current combo multiplier
+    Combo(u32),
+    /// Time passed in a run
+    Tick(f32),
+}
+
+/// Tracks stats from game events and unlocks the achievements whose
+/// requirements they meet.
+///
+/// Unlocks and lifetime stats are saved in `ACHIEVEMENTS_FILE` next to the
+/// high score as `key=value` lines.
+pub struct Achievements<'a> {
+    resource_manager: &'a ResourceManager,
+    stats: HashMap<String, f32>,
+    unlocked: HashSet<String>,
+    /// Unlocked since the last `take_unlocked`
+    newly_unlocked: Vec<String>,
+}
+
+impl<'a> Achievements<'a> {
+    pub fn load(resource_manager: &'a ResourceManager) -> Self {
+        let mut achievements = Achievements {
+            resource_manager,
+            stats: HashMap::new(),
+            unlocked: HashSet::new(),
+            newly_unlocked: vec![],
+        };
+        let Ok(content) = fs::read_to_string(ACHIEVEMENTS_FILE) else {
+            return achievements;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if let Some(id) = key.strip_prefix("unlocked.") {
+                if value == "true" {
+                    achievements.unlocked.insert(id.to_string());
+                }
+            } else if let Some(stat) = key.strip_prefix("stat.")
+                && LIFETIME_STATS.contains(&stat)
+                && let Ok(value) = value.parse::<f32>()
+            {
+                achievements.stats.insert(stat.to_string(), value);
+            }
+        }
+        achievements
+    }
+
+    pub fn save(&self) {
+        let mut content = String::new();
+        for stat in LIFETIME_STATS {
+            content.push_str(&format!("stat.{}={}\n", stat, self.stat(stat)));
+        }
+        let mut unlocked: Vec<_> = self.unlocked.iter().collect();
+        unlocked.sort();
+        for id in unlocked {
+            content.push_str(&format!("unlocked.{}=true\n", id));
+        }
+        fs::write(ACHIEVEMENTS_FILE, content).ok();
+    }
+
+    pub fn record(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::RunStarted => {
+                for stat in RUN_STATS {
+                    self.stats.remove(*stat);
+                }
+                self.add("runs", 1.0);
+            }
+            GameEvent::Shot => {
+                self.add("shots", 1.0);
+                self.stats.insert("seconds_since_shot".to_string(), 0.0);
+            }
+            GameEvent::Kill { big } => {
+                self.add("kills", 1.0);
+                if big {
+                    self.add("big_kills", 1.0);
+                }
+            }
+            GameEvent::Scored(score) => {
+                self.stats.insert("score".to_string(), score as f32);
+            }
+            GameEvent::Combo(multiplier) => {
+                let best = self.stat("multiplier").max(multiplier as f32);
+                self.stats.insert("multiplier".to_string(), best);
+            }
+            GameEvent::Tick(delta_time) => self.add("seconds_since_shot", delta_time),
+        }
+        self.check_unlocks();
+    }
+
+    /// Ids of the achievements unlocked since the last call.
+    pub fn take_unlocked(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.newly_unlocked)
+    }
+
+    pub fn definitions(&self) -> &'a [AchievementDefinition] {
+        self.resource_manager.get_achievements()
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    /// Value and target of the first requirement when it's a lifetime stat,
+    /// progress on run stats is lost between runs.
+    pub fn progress(&self, achievement: &AchievementDefinition) -> Option<(f32, f32)> {
+        let requirement = achievement.requirements.first()?;
+        if !LIFETIME_STATS.contains(&requirement.stat.as_str()) {
+            return None;
+        }
+        Some((self.stat(&requirement.stat).min(requirement.at_least), requirement.at_least))
+    }
+
+    fn stat(&self, name: &str) -> f32 {
+        self.stats.get(name).copied().unwrap_or(0.0)
+    }
+
+    fn add(&mut self, name: &str, amount: f32) {
+        *self.stats.entry(name.to_string()).or_insert(0.0) += amount;
+    }
+
+    fn check_unlocks(&mut self) {
+        let mut changed = false;
+        for achievement in self.definitions() {
+            if self.unlocked.contains(&achievement.id) {
+                continue;
+            }
+            let met = achievement.requirements
+                .iter()
+                .all(|requirement| self.stat(&requirement.stat) >= requirement.at_least);
+            if met {
+                self.unlocked.insert(achievement.id.clone());
+                self.newly_unlocked.push(achievement.id.clone());
+                changed = true;
+            }
+        }
+        if changed {
+            self.save();
+        }
+    }
+}
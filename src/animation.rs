@@ -0,0 +1,247 @@
+use std::rc::Rc;
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+
+// Subset of the JSON written by Aseprite's "Export Sprite Sheet" with the
+// "Array" frames layout and frame tags enabled.
+#[derive(DeJson)]
+struct AsepriteRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+#[derive(DeJson)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    /// Milliseconds
+    duration: u32,
+}
+
+#[derive(DeJson)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[nserde(default)]
+    direction: String,
+}
+
+#[derive(DeJson)]
+struct AsepriteMeta {
+    #[nserde(rename = "frameTags")]
+    #[nserde(default)]
+    frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(DeJson)]
+struct AsepriteSheet {
+    frames: Vec<AsepriteFrame>,
+    meta: AsepriteMeta,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Frame {
+    pub source_rect: Rect,
+    /// Seconds
+    pub duration: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: Direction,
+}
+
+impl Tag {
+    fn len(&self) -> usize {
+        self.to - self.from + 1
+    }
+}
+
+/// Frames of a texture and the named animations (tags) made of them.
+pub struct SpriteSheet {
+    frames: Vec<Frame>,
+    tags: Vec<Tag>,
+}
+
+impl SpriteSheet {
+    pub fn from_aseprite_json(json: &str) -> Result<Self, String> {
+        let sheet = AsepriteSheet::deserialize_json(json).map_err(|err| err.to_string())?;
+        if sheet.frames.is_empty() {
+            return Err("Sprite sheet has no frames".to_string());
+        }
+        let frames: Vec<Frame> = sheet.frames
+            .iter()
+            .map(|frame| Frame {
+                source_rect: Rect::new(frame.frame.x, frame.frame.y, frame.frame.w, frame.frame.h),
+                duration: frame.duration as f32 / 1000.0,
+            })
+            .collect();
+        let mut tags: Vec<Tag> = sheet.meta.frame_tags
+            .into_iter()
+            .filter(|tag| tag.from <= tag.to && tag.to < frames.len())
+            .map(|tag| Tag {
+                direction: match tag.direction.as_str() {
+                    "reverse" => Direction::Reverse,
+                    "pingpong" => Direction::PingPong,
+                    _ => Direction::Forward,
+                },
+                name: tag.name,
+                from: tag.from,
+                to: tag.to,
+            })
+            .collect();
+        // Untagged sheets play all frames in a loop
+        if tags.is_empty() {
+            tags.push(Tag {
+                name: "default".to_string(),
+                from: 0,
+                to: frames.len() - 1,
+                direction: Direction::Forward,
+            });
+        }
+        Ok(SpriteSheet { frames, tags })
+    }
+
+    pub fn tag_index(&self, name: &str) -> Option<usize> {
+        self.tags.iter().position(|tag| tag.name == name)
+    }
+}
+
+pub struct AnimationFrame {
+    pub source_rect: Rect,
+    pub dest_size: Vec2,
+}
+
+/// Plays the tags of a shared `SpriteSheet` using the per-frame durations.
+#[derive(Clone)]
+pub struct AnimatedSprite {
+    sheet: Rc<SpriteSheet>,
+    tag: usize,
+    /// Position within the tag's playback sequence
+    step: usize,
+    elapsed: f32,
+    looping: bool,
+    finished: bool,
+}
+
+impl AnimatedSprite {
+    pub fn new(sheet: Rc<SpriteSheet>) -> Self {
+        AnimatedSprite {
+            sheet,
+            tag: 0,
+            step: 0,
+            elapsed: 0.0,
+            looping: true,
+            finished: false,
+        }
+    }
+
+    /// Switches to the named animation, restarting it only if it wasn't playing already.
+    /// Returns `false` if the sheet has no such animation.
+    pub fn set_animation(&mut self, name: &str) -> bool {
+        match self.sheet.tag_index(name) {
+            Some(tag) => {
+                if tag != self.tag {
+                    self.tag = tag;
+                    self.restart();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Name of the animation playing.
+    pub fn animation(&self) -> &str {
+        &self.sheet.tags[self.tag].name
+    }
+
+    pub fn restart(&mut self) {
+        self.step = 0;
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+
+    /// A sprite that doesn't loop stops on the last frame of its animation.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Advances the animation, returns how many frames were stepped over.
+    pub fn update(&mut self, delta_time: f32) -> usize {
+        if self.finished {
+            return 0;
+        }
+        let mut advanced = 0;
+        self.elapsed += delta_time;
+        loop {
+            let duration = self.sheet.frames[self.frame_index()].duration;
+            if duration <= 0.0 || self.elapsed < duration {
+                break;
+            }
+            if !self.looping && self.step + 1 == self.sequence_len() {
+                self.finished = true;
+                break;
+            }
+            self.elapsed -= duration;
+            self.step = (self.step + 1) % self.sequence_len();
+            advanced += 1;
+        }
+        advanced
+    }
+
+    pub fn frame(&self) -> AnimationFrame {
+        let source_rect = self.sheet.frames[self.frame_index()].source_rect;
+        AnimationFrame {
+            source_rect,
+            dest_size: source_rect.size(),
+        }
+    }
+
+    /// Frame number within the current animation, the first frame being 0.
+    pub fn animation_frame(&self) -> usize {
+        self.frame_index() - self.sheet.tags[self.tag].from
+    }
+
+    /// Index into the sheet's frames for the current step.
+    fn frame_index(&self) -> usize {
+        let tag = &self.sheet.tags[self.tag];
+        let offset = match tag.direction {
+            Direction::Forward => self.step,
+            Direction::Reverse => tag.len() - 1 - self.step,
+            Direction::PingPong => {
+                if self.step < tag.len() {
+                    self.step
+                } else {
+                    2 * (tag.len() - 1) - self.step
+                }
+            }
+        };
+        tag.from + offset
+    }
+
+    fn sequence_len(&self) -> usize {
+        let tag = &self.sheet.tags[self.tag];
+        match tag.direction {
+            // The turning frames are only shown once
+            Direction::PingPong => (2 * tag.len()).saturating_sub(2).max(1),
+            _ => tag.len(),
+        }
+    }
+}
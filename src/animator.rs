@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use super::animation::AnimatedSprite;
+
+/// Test a transition checks against the animator's parameters and playback.
+#[derive(Clone, Debug)]
+pub enum Condition {
+    Less(String, f32),
+    Greater(String, f32),
+    Equals(String, f32),
+    /// Seconds spent in the current state
+    TimeInState(f32),
+}
+
+pub struct State {
+    name: String,
+    animation: String,
+    looping: bool,
+    /// State entered once a one-shot animation has finished
+    next: Option<String>,
+    /// Event names keyed by the animation frame they fire on
+    frame_events: Vec<(usize, String)>,
+}
+
+impl State {
+    /// A state that repeats its animation until a transition leaves it.
+    pub fn looping(name: &str, animation: &str) -> Self {
+        State {
+            name: name.to_string(),
+            animation: animation.to_string(),
+            looping: true,
+            next: None,
+            frame_events: vec![],
+        }
+    }
+
+    /// A state that plays its animation once and then returns to `next`.
+    pub fn once(name: &str, animation: &str, next: &str) -> Self {
+        State {
+            looping: false,
+            next: Some(next.to_string()),
+            ..State::looping(name, animation)
+        }
+    }
+
+    pub fn on_frame(mut self, frame: usize, event: &str) -> Self {
+        self.frame_events.push((frame, event.to_string()));
+        self
+    }
+}
+
+pub struct StateTransition {
+    /// States the transition can be taken from, any state if empty
+    from: Vec<String>,
+    to: String,
+    conditions: Vec<Condition>,
+}
+
+impl StateTransition {
+    pub fn new(from: &[&str], to: &str) -> Self {
+        StateTransition {
+            from: from.iter().map(|name| name.to_string()).collect(),
+            to: to.to_string(),
+            conditions: vec![],
+        }
+    }
+
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnimationEvent {
+    Entered(String),
+    Finished(String),
+    /// Frame event declared with `State::on_frame`, with the state it belongs to
+    Frame(String, String),
+}
+
+/// Picks the animation of a sprite from named states and the transitions
+/// between them.
+///
+/// Transitions are checked in the order they were added and the first one
+/// whose conditions all hold is taken, at most one per update.
+pub struct Animator {
+    states: Vec<State>,
+    transitions: Vec<StateTransition>,
+    current: usize,
+    time_in_state: f32,
+    params: HashMap<String, f32>,
+    /// Cleared when the initial state has been entered on the first update
+    pending_enter: bool,
+    finished: bool,
+}
+
+impl Animator {
+    /// The first state added is the initial one.
+    pub fn new(states: Vec<State>, transitions: Vec<StateTransition>) -> Self {
+        assert!(!states.is_empty(), "Animator needs at least one state");
+        Animator {
+            states,
+            transitions,
+            current: 0,
+            time_in_state: 0.0,
+            params: HashMap::new(),
+            pending_enter: true,
+            finished: false,
+        }
+    }
+
+    pub fn set_param(&mut self, name: &str, value: f32) {
+        self.params.insert(name.to_string(), value);
+    }
+
+    pub fn state(&self) -> &str {
+        &self.states[self.current].name
+    }
+
+    /// Applies transitions and advances the sprite, returns what happened in order.
+    pub fn update(&mut self, sprite: &mut AnimatedSprite, delta_time: f32) -> Vec<AnimationEvent> {
+        let mut events = vec![];
+        if self.pending_enter {
+            self.enter(self.current, sprite, &mut events);
+        }
+
+        self.time_in_state += delta_time;
+        if let Some(target) = self.find_transition() {
+            self.enter(target, sprite, &mut events);
+        }
+
+        if sprite.update(delta_time) > 0 {
+            self.frame_events(sprite.animation_frame(), &mut events);
+        }
+        if sprite.is_finished() && !self.finished {
+            self.finished = true;
+            events.push(AnimationEvent::Finished(self.state().to_string()));
+            if let Some(next) = self.states[self.current].next.as_deref()
+                && let Some(target) = self.state_index(next)
+            {
+                self.enter(target, sprite, &mut events);
+            }
+        }
+        events
+    }
+
+    fn find_transition(&self) -> Option<usize> {
+        let current = self.state();
+        self.transitions
+            .iter()
+            .filter(|transition| transition.to != current)
+            .filter(|transition| {
+                transition.from.is_empty() || transition.from.iter().any(|from| from == current)
+            })
+            .find(|transition| {
+                transition.conditions.iter().all(|condition| self.holds(condition))
+            })
+            .and_then(|transition| self.state_index(&transition.to))
+    }
+
+    fn holds(&self, condition: &Condition) -> bool {
+        let param = |name: &String| self.params.get(name).copied().unwrap_or(0.0);
+        match condition {
+            Condition::Less(name, value) => param(name) < *value,
+            Condition::Greater(name, value) => param(name) > *value,
+            Condition::Equals(name, value) => (param(name) - value).abs() < f32::EPSILON,
+            Condition::TimeInState(seconds) => self.time_in_state >= *seconds,
+        }
+    }
+
+    fn enter(&mut self, index: usize, sprite: &mut AnimatedSprite, events: &mut Vec<AnimationEvent>) {
+        self.current = index;
+        self.time_in_state = 0.0;
+        self.pending_enter = false;
+        self.finished = false;
+        let state = &self.states[index];
+        if !sprite.set_animation(&state.animation) {
+            warn!("Unknown animation {} for state {}", state.animation, state.name);
+        }
+        sprite.restart();
+        sprite.set_looping(state.looping);
+        events.push(AnimationEvent::Entered(state.name.clone()));
+        self.frame_events(sprite.animation_frame(), events);
+    }
+
+    fn frame_events(&self, frame: usize, events: &mut Vec<AnimationEvent>) {
+        let state = &self.states[self.current];
+        for (_, event) in state.frame_events.iter().filter(|(at, _)| *at == frame) {
+            events.push(AnimationEvent::Frame(state.name.clone(), event.clone()));
+        }
+    }
+
+    fn state_index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state.name == name)
+    }
+}
@@ -0,0 +1,185 @@
+use std::f32::consts::PI;
+use macroquad::prelude::*;
+use super::game_object::GameObject;
+use super::resource_manager::{self, ResourceManager};
+use super::rng::Rng;
+
+/// Widest angle from straight down asteroids drift in at
+const MAX_DRIFT_ANGLE: f32 = PI / 5.0;
+/// Radians fragments fly apart from the direction of the rock they broke off
+const FRAGMENT_SPREAD: f32 = 0.6;
+/// Fragments are faster than the rock they broke off
+const FRAGMENT_SPEEDUP: f32 = 1.3;
+/// Mass of a ship, against the masses of the sizes
+const SHIP_MASS: f32 = 2.0;
+/// Mass of a rock that does the damage of an enemy
+const REFERENCE_MASS: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    /// Width and height on the playfield.
+    pub fn diameter(self) -> f32 {
+        match self {
+            AsteroidSize::Large => 96.0,
+            AsteroidSize::Medium => 64.0,
+            AsteroidSize::Small => 40.0,
+        }
+    }
+
+    pub fn mass(self) -> f32 {
+        match self {
+            AsteroidSize::Large => 6.0,
+            AsteroidSize::Medium => 3.0,
+            AsteroidSize::Small => 1.0,
+        }
+    }
+
+    /// Hits it takes to break.
+    pub fn health(self) -> u32 {
+        match self {
+            AsteroidSize::Large => 4,
+            AsteroidSize::Medium => 2,
+            AsteroidSize::Small => 1,
+        }
+    }
+
+    /// Points for breaking it, before combo and multipliers.
+    pub fn points(self) -> u32 {
+        match self {
+            AsteroidSize::Large => 60,
+            AsteroidSize::Medium => 40,
+            AsteroidSize::Small => 20,
+        }
+    }
+
+    /// Size and number of the fragments it splits into, `None` for the smallest.
+    fn fragments(self) -> Option<(AsteroidSize, usize)> {
+        match self {
+            AsteroidSize::Large => Some((AsteroidSize::Medium, 2)),
+            AsteroidSize::Medium => Some((AsteroidSize::Small, 3)),
+            AsteroidSize::Small => None,
+        }
+    }
+}
+
+/// Rock drifting through the playfield at an angle, spinning as it goes.
+/// Shots don't go through it, it takes a few to break into fragments.
+pub struct Asteroid {
+    pub object: GameObject,
+    pub size: AsteroidSize,
+    /// Pixels per second
+    pub velocity: Vec2,
+    /// Radians per second
+    pub spin: f32,
+    /// Hits left until it breaks
+    pub health: u32,
+    /// Seconds left of the flash after a hit it survived
+    pub flash: f32,
+    /// Scatters its fragments, seeded when it spawned so breaking it doesn't
+    /// change what spawns next
+    rng: Rng,
+}
+
+impl Asteroid {
+    pub fn new(resource_manager: &ResourceManager, size: AsteroidSize, position: Vec2, velocity: Vec2, spin: f32, rng: Rng) -> Self {
+        let texture_id = resource_manager::constants::ASTEROID_TEX_ID;
+        Asteroid {
+            object: GameObject {
+                width: size.diameter(),
+                height: size.diameter(),
+                speed: velocity.length(),
+                x: position.x,
+                y: position.y,
+                rotation: 0.0,
+                collided: false,
+                texture_id: texture_id.to_string(),
+                sprite: resource_manager.get_animated_sprite(texture_id).unwrap(),
+                animator: None,
+            },
+            size,
+            velocity,
+            spin,
+            health: size.health(),
+            flash: 0.0,
+            rng,
+        }
+    }
+
+    /// Large asteroid drifting in from above the playfield at a random angle.
+    pub fn spawn(resource_manager: &ResourceManager, rng: &mut Rng, playfield_width: f32, speed_mult: f32) -> Self {
+        let size = AsteroidSize::Large;
+        let position = vec2(rng.gen_range(0.0, playfield_width), -size.diameter());
+        let angle = rng.gen_range(-MAX_DRIFT_ANGLE, MAX_DRIFT_ANGLE);
+        let velocity = Vec2::from_angle(angle).rotate(Vec2::Y) * rng.gen_range(80.0, 160.0) * speed_mult;
+        let spin = rng.gen_range(-2.0, 2.0);
+        Asteroid::new(resource_manager, size, position, velocity, spin, Rng::new(rng.next_u64()))
+    }
+
+    pub fn mass(&self) -> f32 {
+        self.size.mass()
+    }
+
+    pub fn position(&self) -> Vec2 {
+        vec2(self.object.x, self.object.y)
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.object.x += self.velocity.x * delta_time;
+        self.object.y += self.velocity.y * delta_time;
+        self.object.rotation = (self.object.rotation + self.spin * delta_time) % (2.0 * PI);
+        self.flash = (self.flash - delta_time).max(0.0);
+    }
+
+    /// Fragments it breaks into, fanned out around its direction.
+    pub fn split(&mut self, resource_manager: &ResourceManager) -> Vec<Asteroid> {
+        let Some((size, count)) = self.size.fragments() else {
+            return vec![];
+        };
+        let velocity = self.velocity.normalize_or(Vec2::Y) * self.velocity.length().max(80.0) * FRAGMENT_SPEEDUP;
+        (0..count)
+            .map(|index| {
+                let share = if count > 1 { index as f32 / (count - 1) as f32 * 2.0 - 1.0 } else { 0.0 };
+                let direction = Vec2::from_angle(share * FRAGMENT_SPREAD + self.rng.gen_range(-0.1, 0.1));
+                let fragment_velocity = direction.rotate(velocity);
+                let position = self.position() + fragment_velocity.normalize_or(Vec2::Y) * size.diameter() / 3.0;
+                let spin = self.rng.gen_range(-3.0, 3.0);
+                let rng = Rng::new(self.rng.next_u64());
+                Asteroid::new(resource_manager, size, position, fragment_velocity, spin, rng)
+            })
+            .collect()
+    }
+
+    /// Bounces it off a ship at `ship` flying with `ship_velocity`, as an
+    /// elastic collision by mass. Returns the velocity the ship gets knocked into.
+    pub fn bounce(&mut self, ship: Vec2, ship_velocity: Vec2) -> Vec2 {
+        let normal = (ship - self.position()).normalize_or(Vec2::Y);
+        let closing = (self.velocity - ship_velocity).dot(normal);
+        if closing <= 0.0 {
+            return Vec2::ZERO;
+        }
+        let impulse = 2.0 * closing / (1.0 / self.mass() + 1.0 / SHIP_MASS);
+        self.velocity -= normal * impulse / self.mass();
+        normal * impulse / SHIP_MASS
+    }
+
+    /// Damage it does to a ship it runs into, the damage of an enemy scaled
+    /// by its mass.
+    pub fn damage(&self, enemy_damage: u32) -> u32 {
+        ((enemy_damage as f32 * self.mass() / REFERENCE_MASS).round() as u32).max(1)
+    }
+
+    /// Gone past the edges of the playfield for good.
+    pub fn is_lost(&self, playfield: Vec2) -> bool {
+        let margin = self.object.width * 2.0;
+        self.object.x < -margin
+            || self.object.x > playfield.x + margin
+            || self.object.y < -margin
+            || self.object.y > playfield.y + margin
+    }
+}
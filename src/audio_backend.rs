@@ -0,0 +1,226 @@
+#[cfg(test)]
+use std::collections::HashMap;
+use std::fmt;
+use macroquad::audio::{play_sound, stop_sound, set_sound_volume, PlaySoundParams};
+use super::resource_manager::ResourceManager;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioError {
+    UnknownSound(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::UnknownSound(id) => write!(f, "Unknown sound id: {}", id),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoiceParams {
+    pub volume: f32,
+    pub pan: f32,
+    pub pitch: f32,
+    pub looped: bool,
+}
+
+/// A started sound as reported by the backend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Voice {
+    /// Backend specific channel that has to be stopped to silence this voice
+    pub channel: usize,
+    pub duration: f64,
+}
+
+pub trait AudioBackend {
+    fn play(&mut self, id: &str, params: VoiceParams) -> Result<Voice, AudioError>;
+    /// Stops the given channel of a sound or every instance of it when `channel` is `None`.
+    fn stop(&mut self, id: &str, channel: Option<usize>) -> Result<(), AudioError>;
+    fn set_volume(&mut self, id: &str, volume: f32) -> Result<(), AudioError>;
+}
+
+pub struct MacroquadBackend<'a> {
+    resource_manager: &'a ResourceManager,
+}
+
+impl<'a> MacroquadBackend<'a> {
+    pub fn new(resource_manager: &'a ResourceManager) -> Self {
+        MacroquadBackend { resource_manager }
+    }
+}
+
+impl AudioBackend for MacroquadBackend<'_> {
+    fn play(&mut self, id: &str, params: VoiceParams) -> Result<Voice, AudioError> {
+        if let (false, Some(bank)) = (params.looped, self.resource_manager.get_sound_bank(id)) {
+            let channel = bank.variant_index(params.pan, params.pitch);
+            play_sound(
+                bank.variant(channel),
+                PlaySoundParams {
+                    looped: false,
+                    volume: params.volume,
+                }
+            );
+            return Ok(Voice {
+                channel,
+                duration: bank.duration(channel),
+            });
+        }
+
+        let sound = self.resource_manager.get_sound(id)
+            .ok_or_else(|| AudioError::UnknownSound(id.to_string()))?;
+        play_sound(
+            sound,
+            PlaySoundParams {
+                looped: params.looped,
+                volume: params.volume,
+            }
+        );
+        Ok(Voice {
+            channel: 0,
+            duration: 0.0,
+        })
+    }
+
+    fn stop(&mut self, id: &str, channel: Option<usize>) -> Result<(), AudioError> {
+        match (channel, self.resource_manager.get_sound_bank(id)) {
+            (Some(channel), Some(bank)) => stop_sound(bank.variant(channel)),
+            (None, Some(bank)) => {
+                for channel in 0..bank.variant_count() {
+                    stop_sound(bank.variant(channel));
+                }
+            }
+            _ => {
+                let sound = self.resource_manager.get_sound(id)
+                    .ok_or_else(|| AudioError::UnknownSound(id.to_string()))?;
+                stop_sound(sound);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_volume(&mut self, id: &str, volume: f32) -> Result<(), AudioError> {
+        let sound = self.resource_manager.get_sound(id)
+            .ok_or_else(|| AudioError::UnknownSound(id.to_string()))?;
+        set_sound_volume(sound, volume);
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend + ?Sized> AudioBackend for Box<B> {
+    fn play(&mut self, id: &str, params: VoiceParams) -> Result<Voice, AudioError> {
+        (**self).play(id, params)
+    }
+
+    fn stop(&mut self, id: &str, channel: Option<usize>) -> Result<(), AudioError> {
+        (**self).stop(id, channel)
+    }
+
+    fn set_volume(&mut self, id: &str, volume: f32) -> Result<(), AudioError> {
+        (**self).set_volume(id, volume)
+    }
+}
+
+/// Plays nothing, for headless runs without an audio device.
+#[derive(Default)]
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn play(&mut self, _id: &str, _params: VoiceParams) -> Result<Voice, AudioError> {
+        Ok(Voice {
+            channel: 0,
+            duration: 0.0,
+        })
+    }
+
+    fn stop(&mut self, _id: &str, _channel: Option<usize>) -> Result<(), AudioError> {
+        Ok(())
+    }
+
+    fn set_volume(&mut self, _id: &str, _volume: f32) -> Result<(), AudioError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioEvent {
+    Played { id: String, params: VoiceParams },
+    Stopped { id: String, channel: Option<usize> },
+    VolumeChanged { id: String, volume: f32 },
+}
+
+/// Remembers every request instead of playing it, so tests can assert on what
+/// the game tried to play. Only the registered sound ids are accepted.
+#[cfg(test)]
+pub struct RecordingBackend {
+    durations: HashMap<String, f64>,
+    events: Vec<AudioEvent>,
+    next_channel: usize,
+}
+
+#[cfg(test)]
+impl RecordingBackend {
+    pub fn new(ids: &[&str]) -> Self {
+        RecordingBackend {
+            durations: ids.iter().map(|id| (id.to_string(), 1.0)).collect(),
+            events: vec![],
+            next_channel: 0,
+        }
+    }
+
+    pub fn set_duration(&mut self, id: &str, duration: f64) {
+        self.durations.insert(id.to_string(), duration);
+    }
+
+    pub fn events(&self) -> &[AudioEvent] {
+        &self.events
+    }
+
+    pub fn play_count(&self, id: &str) -> usize {
+        self.events
+            .iter()
+            .filter(|event| matches!(event, AudioEvent::Played { id: played, .. } if played == id))
+            .count()
+    }
+
+    pub fn was_played(&self, id: &str) -> bool {
+        self.play_count(id) > 0
+    }
+
+    fn check(&self, id: &str) -> Result<(), AudioError> {
+        if self.durations.contains_key(id) {
+            Ok(())
+        } else {
+            Err(AudioError::UnknownSound(id.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+impl AudioBackend for RecordingBackend {
+    fn play(&mut self, id: &str, params: VoiceParams) -> Result<Voice, AudioError> {
+        self.check(id)?;
+        self.events.push(AudioEvent::Played { id: id.to_string(), params });
+        let channel = self.next_channel;
+        self.next_channel += 1;
+        Ok(Voice {
+            channel,
+            duration: self.durations[id],
+        })
+    }
+
+    fn stop(&mut self, id: &str, channel: Option<usize>) -> Result<(), AudioError> {
+        self.check(id)?;
+        self.events.push(AudioEvent::Stopped { id: id.to_string(), channel });
+        Ok(())
+    }
+
+    fn set_volume(&mut self, id: &str, volume: f32) -> Result<(), AudioError> {
+        self.check(id)?;
+        self.events.push(AudioEvent::VolumeChanged { id: id.to_string(), volume });
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::str::FromStr;
+use macroquad::prelude::*;
+use super::game_object::GameObject;
+
+/// Share of its speed an enemy flies in with
+const ENTER_SPEED: f32 = 0.6;
+/// Seconds a kamikaze holds still before it dives
+const AIM_SECONDS: f32 = 0.6;
+/// Dive speed as a share of the normal speed
+const DIVE_SPEED: f32 = 1.4;
+/// Radians a second a diving kamikaze turns towards its target
+const DIVE_TURN_RATE: f32 = 2.0;
+/// Seconds a strafer follows the ships before it leaves
+const STRAFE_SECONDS: f32 = 8.0;
+/// Share of its speed a strafer follows the ships with
+const STRAFE_SPEED: f32 = 0.5;
+/// Seconds between two shots of a strafer
+const FIRE_INTERVAL: f32 = 1.0;
+/// Hits a shield takes on top of the enemy health
+const SHIELD_HITS: u32 = 2;
+/// Share of its speed a shielded enemy advances with
+const ADVANCE_SPEED: f32 = 0.35;
+/// Share of its speed a shielded enemy backs off with
+const RETREAT_SPEED: f32 = 0.6;
+const RETREAT_SECONDS: f32 = 0.8;
+/// Seconds between two enemies released by a spawner
+const RELEASE_INTERVAL: f32 = 2.0;
+/// Enemies a spawner releases before it leaves
+const RELEASES: u32 = 4;
+
+/// What an enemy with a mind of its own does, carried out by a `Brain`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behaviour {
+    /// Takes aim and dives into the nearest ship
+    Kamikaze,
+    /// Follows the nearest ship from side to side and fires at it
+    Strafer,
+    /// Takes extra hits and backs off after each one
+    Shielded,
+    /// Hovers at the top releasing smaller enemies
+    Spawner,
+}
+
+impl FromStr for Behaviour {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kamikaze" => Ok(Behaviour::Kamikaze),
+            "strafer" => Ok(Behaviour::Strafer),
+            "shielded" => Ok(Behaviour::Shielded),
+            "spawner" => Ok(Behaviour::Spawner),
+            _ => Err(format!("Unknown behaviour: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Behaviour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Behaviour::Kamikaze => "kamikaze",
+            Behaviour::Strafer => "strafer",
+            Behaviour::Shielded => "shielded",
+            Behaviour::Spawner => "spawner",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Behaviour {
+    /// Hits it takes on top of the tuning's enemy health.
+    pub fn extra_health(self) -> u32 {
+        match self {
+            Behaviour::Shielded => SHIELD_HITS,
+            _ => 0,
+        }
+    }
+
+    /// Fraction of the playfield height it flies in to before it gets going.
+    fn post(self) -> f32 {
+        match self {
+            Behaviour::Kamikaze => 0.2,
+            Behaviour::Strafer => 0.15,
+            Behaviour::Shielded => 0.0,
+            Behaviour::Spawner => 0.12,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    /// Flies in from the top to its post
+    Enter,
+    /// Holds still, lining up a dive
+    Aim,
+    /// Homes in on the target
+    Dive,
+    /// Follows the target from side to side and fires
+    Strafe,
+    /// Sinks towards the ships
+    Advance,
+    /// Backs off after a hit
+    Retreat,
+    /// Hovers releasing smaller enemies
+    Release,
+    /// Done, flies down and off the playfield
+    Leave,
+}
+
+/// What an enemy does besides moving, carried out by the scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Shoots straight down
+    Fire,
+    /// Releases a smaller enemy where it is
+    Release,
+}
+
+/// State machine steering a `GameObject` by its behaviour and what it sees
+/// of the ships.
+#[derive(Clone, Debug)]
+pub struct Brain {
+    behaviour: Behaviour,
+    state: State,
+    /// Seconds in the current state
+    time: f32,
+    /// Seconds until the next shot or release
+    cooldown: f32,
+    /// Enemies left to release
+    releases: u32,
+    velocity: Vec2,
+}
+
+impl Brain {
+    pub fn new(behaviour: Behaviour) -> Self {
+        Brain {
+            behaviour,
+            state: if behaviour == Behaviour::Shielded { State::Advance } else { State::Enter },
+            time: 0.0,
+            cooldown: 0.0,
+            releases: RELEASES,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    pub fn behaviour(&self) -> Behaviour {
+        self.behaviour
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// The enemy took a hit it survived.
+    pub fn damaged(&mut self) {
+        if self.behaviour == Behaviour::Shielded && self.state != State::Leave {
+            self.switch(State::Retreat);
+        }
+    }
+
+    /// Moves the object for one step, `target` is the nearest ship if any
+    /// is still flying. Returns what the enemy does besides moving.
+    pub fn update(&mut self, object: &mut GameObject, target: Option<Vec2>, delta_time: f32, playfield: Vec2) -> Option<Action> {
+        self.time += delta_time;
+        self.cooldown -= delta_time;
+        let position = vec2(object.x, object.y);
+        let speed = object.speed;
+        let mut action = None;
+        match self.state {
+            State::Enter => {
+                object.y += speed * ENTER_SPEED * delta_time;
+                if object.y >= playfield.y * self.behaviour.post() {
+                    self.switch(match self.behaviour {
+                        Behaviour::Kamikaze => State::Aim,
+                        Behaviour::Strafer => State::Strafe,
+                        Behaviour::Spawner => State::Release,
+                        Behaviour::Shielded => State::Advance,
+                    });
+                    self.cooldown = FIRE_INTERVAL / 2.0;
+                }
+            }
+            State::Aim => {
+                if self.time >= AIM_SECONDS {
+                    let direction = target.map_or(Vec2::Y, |target| (target - position).normalize_or(Vec2::Y));
+                    self.velocity = direction * speed * DIVE_SPEED;
+                    self.switch(State::Dive);
+                }
+            }
+            State::Dive => {
+                // Only ships still ahead are followed, a missed dive carries on
+                if let Some(target) = target.filter(|target| target.y > position.y) {
+                    let turn = DIVE_TURN_RATE * delta_time;
+                    let angle = self.velocity.angle_between(target - position).clamp(-turn, turn);
+                    self.velocity = Vec2::from_angle(angle).rotate(self.velocity);
+                }
+                // Never back up, it has to leave the playfield at the bottom
+                self.velocity.y = self.velocity.y.max(speed * 0.3);
+                object.x += self.velocity.x * delta_time;
+                object.y += self.velocity.y * delta_time;
+            }
+            State::Strafe => {
+                if let Some(target) = target {
+                    let step = speed * STRAFE_SPEED * delta_time;
+                    let offset = target.x - position.x;
+                    object.x += offset.clamp(-step, step);
+                    if self.cooldown <= 0.0 && offset.abs() < object.width {
+                        self.cooldown = FIRE_INTERVAL;
+                        action = Some(Action::Fire);
+                    }
+                }
+                if self.time >= STRAFE_SECONDS {
+                    self.switch(State::Leave);
+                }
+            }
+            State::Advance => {
+                object.y += speed * ADVANCE_SPEED * delta_time;
+            }
+            State::Retreat => {
+                // Backs off towards the top without leaving the playfield again
+                let top = object.y.min(object.height / 2.0);
+                object.y = (object.y - speed * RETREAT_SPEED * delta_time).max(top);
+                if self.time >= RETREAT_SECONDS {
+                    self.switch(State::Advance);
+                }
+            }
+            State::Release => {
+                object.x += (self.time * 1.5).cos() * speed * 0.2 * delta_time;
+                object.x = object.x.clamp(object.width / 2.0, playfield.x - object.width / 2.0);
+                if self.cooldown <= 0.0 {
+                    self.cooldown = RELEASE_INTERVAL;
+                    self.releases -= 1;
+                    action = Some(Action::Release);
+                    if self.releases == 0 {
+                        self.switch(State::Leave);
+                    }
+                }
+            }
+            State::Leave => {
+                object.y += speed * delta_time;
+            }
+        }
+        action
+    }
+
+    fn switch(&mut self, state: State) {
+        self.state = state;
+        self.time = 0.0;
+    }
+}
@@ -0,0 +1,27 @@
+#version 100
+
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 iResolution;
+
+vec3 Bright(vec2 coord) {
+    vec3 col = texture2D(Texture, coord).rgb;
+    float luminance = dot(col, vec3(0.2126, 0.7152, 0.0722));
+    return col * smoothstep(0.5, 0.9, luminance);
+}
+
+void main() {
+    vec2 texel = 2.0 / iResolution;
+    vec3 glow = vec3(0);
+    for (int y = -2; y <= 2; y++) {
+        for (int x = -2; x <= 2; x++) {
+            vec2 offset = vec2(float(x), float(y));
+            glow += Bright(uv + offset * texel) * (1.0 - length(offset) / 3.0);
+        }
+    }
+    vec3 col = texture2D(Texture, uv).rgb + glow * 0.12;
+    gl_FragColor = vec4(col, 1.0);
+}
@@ -0,0 +1,90 @@
+use macroquad::prelude::*;
+use super::settings::Settings;
+
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+const MAX_SHAKE_ROTATION: f32 = 1.5;
+const TRAUMA_DECAY_PER_SECOND: f32 = 1.2;
+const KICK_DECAY_PER_SECOND: f32 = 18.0;
+
+/// Screen shake, hit-stop and recoil applied on top of the playfield camera.
+///
+/// Shake is driven by "trauma" in the 0..1 range; the visible shake grows with
+/// the square of it so small hits stay subtle while big ones add up.
+pub struct CameraEffects {
+    trauma: f32,
+    kick: Vec2,
+    hit_stop: f32,
+    intensity: f32,
+    enabled: bool,
+}
+
+impl CameraEffects {
+    pub fn new() -> Self {
+        CameraEffects {
+            trauma: 0.0,
+            kick: Vec2::ZERO,
+            hit_stop: 0.0,
+            intensity: 1.0,
+            enabled: true,
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.intensity = settings.shake_intensity;
+        self.enabled = !settings.reduced_motion;
+        if !self.enabled {
+            self.reset();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.trauma = 0.0;
+        self.kick = Vec2::ZERO;
+        self.hit_stop = 0.0;
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        if self.enabled {
+            self.trauma = (self.trauma + amount).min(1.0);
+        }
+    }
+
+    /// Pushes the camera by `offset` playfield units, it springs back on its own.
+    pub fn kick(&mut self, offset: Vec2) {
+        if self.enabled {
+            self.kick += offset;
+        }
+    }
+
+    /// Freezes gameplay for a few frames to give a hit more weight.
+    pub fn hit_stop(&mut self, seconds: f32) {
+        if self.enabled {
+            self.hit_stop = self.hit_stop.max(seconds);
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.hit_stop > 0.0
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.hit_stop = (self.hit_stop - delta_time).max(0.0);
+        self.trauma = (self.trauma - TRAUMA_DECAY_PER_SECOND * delta_time).max(0.0);
+        self.kick *= (1.0 - KICK_DECAY_PER_SECOND * delta_time).max(0.0);
+    }
+
+    /// Returns the camera displaced by the current shake and kick.
+    pub fn apply(&self, camera: &Camera2D) -> Camera2D {
+        let shake = self.trauma * self.trauma * self.intensity;
+        let offset = vec2(
+            rand::gen_range(-1.0, 1.0) * MAX_SHAKE_OFFSET * shake,
+            rand::gen_range(-1.0, 1.0) * MAX_SHAKE_OFFSET * shake
+        ) + self.kick * self.intensity;
+        Camera2D {
+            target: camera.target + offset,
+            rotation: camera.rotation + rand::gen_range(-1.0, 1.0) * MAX_SHAKE_ROTATION * shake,
+            render_target: camera.render_target.clone(),
+            ..*camera
+        }
+    }
+}
@@ -0,0 +1,16 @@
+#version 100
+
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 iResolution;
+
+void main() {
+    vec2 offset = (uv - 0.5) * 3.0 / iResolution.x;
+    float r = texture2D(Texture, uv + offset).r;
+    float g = texture2D(Texture, uv).g;
+    float b = texture2D(Texture, uv - offset).b;
+    gl_FragColor = vec4(r, g, b, 1.0);
+}
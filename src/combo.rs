@@ -0,0 +1,61 @@
+/// Seconds after a kill in which the next one continues the chain
+const CHAIN_WINDOW_SECONDS: f32 = 2.5;
+/// Kills needed to raise the multiplier by one
+const KILLS_PER_STEP: u32 = 4;
+const MAX_MULTIPLIER: u32 = 8;
+
+/// Chain of kills in quick succession that multiplies the points they give.
+///
+/// Each kill restarts the chain window. When the window runs out the chain
+/// decays by one multiplier step and the window starts over, until the chain
+/// is gone. Taking damage breaks it at once.
+pub struct Combo {
+    chain: u32,
+    time_left: f32,
+}
+
+impl Combo {
+    pub fn new() -> Self {
+        Combo {
+            chain: 0,
+            time_left: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if self.chain == 0 {
+            return;
+        }
+        self.time_left -= delta_time;
+        if self.time_left <= 0.0 {
+            self.chain = self.chain.saturating_sub(KILLS_PER_STEP);
+            self.time_left = if self.chain > 0 { CHAIN_WINDOW_SECONDS } else { 0.0 };
+        }
+    }
+
+    /// Adds a kill to the chain, returns `points` with the multiplier applied.
+    pub fn register_kill(&mut self, points: u32) -> u32 {
+        let awarded = points * self.multiplier();
+        self.chain += 1;
+        self.time_left = CHAIN_WINDOW_SECONDS;
+        awarded
+    }
+
+    pub fn break_chain(&mut self) {
+        self.chain = 0;
+        self.time_left = 0.0;
+    }
+
+    pub fn chain(&self) -> u32 {
+        self.chain
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.chain / KILLS_PER_STEP).min(MAX_MULTIPLIER)
+    }
+
+    /// Share of the chain window that is left, from 1.0 right after a kill down to 0.0.
+    pub fn time_left(&self) -> f32 {
+        self.time_left / CHAIN_WINDOW_SECONDS
+    }
+}
@@ -0,0 +1,81 @@
+use macroquad::prelude::*;
+use super::navigation::Navigation;
+
+/// Keys of one player, a co-op game splits the keyboard between two sets.
+pub struct KeyBindings {
+    pub up: &'static [KeyCode],
+    pub down: &'static [KeyCode],
+    pub left: &'static [KeyCode],
+    pub right: &'static [KeyCode],
+    pub fire: &'static [KeyCode],
+}
+
+/// A single player can use both halves of the keyboard
+pub const SOLO_KEYS: KeyBindings = KeyBindings {
+    up: &[KeyCode::W, KeyCode::Up],
+    down: &[KeyCode::S, KeyCode::Down],
+    left: &[KeyCode::A, KeyCode::Left],
+    right: &[KeyCode::D, KeyCode::Right],
+    fire: &[KeyCode::Space],
+};
+
+pub const PLAYER_ONE_KEYS: KeyBindings = KeyBindings {
+    up: &[KeyCode::W],
+    down: &[KeyCode::S],
+    left: &[KeyCode::A],
+    right: &[KeyCode::D],
+    fire: &[KeyCode::Space],
+};
+
+pub const PLAYER_TWO_KEYS: KeyBindings = KeyBindings {
+    up: &[KeyCode::Up],
+    down: &[KeyCode::Down],
+    left: &[KeyCode::Left],
+    right: &[KeyCode::Right],
+    fire: &[KeyCode::Enter, KeyCode::RightControl],
+};
+
+/// What a player wants to do this frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ControlState {
+    /// Each axis in -1..1, y points down
+    pub movement: Vec2,
+    /// Fire was pressed this frame
+    pub fire: bool,
+}
+
+/// Key bindings and gamepad of one player.
+pub struct PlayerControls {
+    keys: &'static KeyBindings,
+    /// Index of the player's gamepad among the connected ones
+    gamepad: usize,
+}
+
+impl PlayerControls {
+    pub fn new(keys: &'static KeyBindings, gamepad: usize) -> Self {
+        PlayerControls {
+            keys,
+            gamepad,
+        }
+    }
+
+    pub fn set_keys(&mut self, keys: &'static KeyBindings) {
+        self.keys = keys;
+    }
+
+    pub fn read(&self, navigation: &Navigation) -> ControlState {
+        let held = |keys: &[KeyCode]| if keys.iter().any(|key| is_key_down(*key)) { 1.0 } else { 0.0 };
+        let mut state = ControlState {
+            movement: vec2(
+                held(self.keys.right) - held(self.keys.left),
+                held(self.keys.down) - held(self.keys.up)
+            ),
+            fire: self.keys.fire.iter().any(|key| is_key_pressed(*key)),
+        };
+        if let Some(gamepad) = navigation.gamepad(self.gamepad) {
+            state.movement = (state.movement + gamepad.movement).clamp(Vec2::splat(-1.0), Vec2::ONE);
+            state.fire |= gamepad.fire;
+        }
+        state
+    }
+}
@@ -0,0 +1,21 @@
+#version 100
+
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 iResolution;
+
+void main() {
+    // Barrel distortion
+    vec2 centered = uv * 2.0 - 1.0;
+    centered *= 1.0 + dot(centered.yx, centered.yx) * vec2(0.03, 0.04);
+    vec2 curved = centered * 0.5 + 0.5;
+
+    if (curved.x < 0.0 || curved.x > 1.0 || curved.y < 0.0 || curved.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+    } else {
+        gl_FragColor = vec4(texture2D(Texture, curved).rgb, 1.0);
+    }
+}
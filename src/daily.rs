@@ -0,0 +1,183 @@
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use macroquad::miniquad;
+use super::game_mode::Tuning;
+use super::rng::Rng;
+
+const HISTORY_FILE: &str = "daily.dat";
+const MUTATORS_PER_DAY: usize = 2;
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Twist on the rules of a daily challenge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutator {
+    /// Enemies spawn half again as often
+    Swarm,
+    /// Enemies fly faster
+    Speedy,
+    /// Enemies are bigger
+    Giants,
+    /// The ships reload twice as fast
+    RapidFire,
+    /// A single life
+    Fragile,
+    /// Kills are worth double
+    Jackpot,
+}
+
+impl FromStr for Mutator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "swarm" => Ok(Mutator::Swarm),
+            "speedy" => Ok(Mutator::Speedy),
+            "giants" => Ok(Mutator::Giants),
+            "rapid_fire" => Ok(Mutator::RapidFire),
+            "fragile" => Ok(Mutator::Fragile),
+            "jackpot" => Ok(Mutator::Jackpot),
+            _ => Err(format!("Unknown mutator: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Mutator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Mutator::Swarm => "swarm",
+            Mutator::Speedy => "speedy",
+            Mutator::Giants => "giants",
+            Mutator::RapidFire => "rapid_fire",
+            Mutator::Fragile => "fragile",
+            Mutator::Jackpot => "jackpot",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 6] = [
+        Mutator::Swarm,
+        Mutator::Speedy,
+        Mutator::Giants,
+        Mutator::RapidFire,
+        Mutator::Fragile,
+        Mutator::Jackpot,
+    ];
+
+    pub fn apply(self, tuning: &mut Tuning) {
+        match self {
+            Mutator::Swarm => tuning.spawn_chance *= 1.5,
+            Mutator::Speedy => tuning.enemy_speed *= 1.3,
+            Mutator::Giants => tuning.enemy_size *= 1.4,
+            Mutator::RapidFire => tuning.reload_time *= 0.5,
+            Mutator::Fragile => tuning.lives = 1,
+            Mutator::Jackpot => tuning.score_multiplier *= 2.0,
+        }
+    }
+}
+
+/// Mutators of the challenge played on `seed`.
+pub fn mutators(seed: u64) -> Vec<Mutator> {
+    let mut rng = Rng::new(!seed);
+    let mut pool = Mutator::ALL.to_vec();
+    (0..MUTATORS_PER_DAY).map(|_| pool.remove(rng.gen_index(pool.len()))).collect()
+}
+
+/// The same run for everyone on a given day, seed and mutators follow from the date.
+#[derive(Clone, Debug)]
+pub struct DailyChallenge {
+    /// `YYYY-MM-DD` in UTC
+    pub date: String,
+    pub seed: u64,
+    pub mutators: Vec<Mutator>,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        Self::for_day((miniquad::date::now() / SECONDS_PER_DAY).floor() as i64)
+    }
+
+    /// Challenge of the day counted from 1970-01-01.
+    pub fn for_day(day: i64) -> Self {
+        let seed = day as u64;
+        DailyChallenge {
+            date: format_date(day),
+            seed,
+            mutators: mutators(seed),
+        }
+    }
+}
+
+/// `YYYY-MM-DD` of the day counted from 1970-01-01.
+fn format_date(day: i64) -> String {
+    // Civil from days, shifted so years start in March and leap days come last
+    let day = day + 719_468;
+    let era = day.div_euclid(146_097);
+    let day_of_era = day.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+/// Score of every daily challenge played, only the first attempt of a day counts.
+///
+/// Stored in `HISTORY_FILE` as `date=score` lines.
+#[derive(Clone, Debug, Default)]
+pub struct DailyHistory {
+    /// Newest day first
+    entries: Vec<(String, u32)>,
+}
+
+impl DailyHistory {
+    pub fn load() -> Self {
+        let mut history = DailyHistory::default();
+        let Ok(content) = fs::read_to_string(HISTORY_FILE) else {
+            return history;
+        };
+        for line in content.lines() {
+            if let Some((date, score)) = line.split_once('=')
+                && let Ok(score) = score.trim().parse()
+            {
+                history.record(date.trim(), score);
+            }
+        }
+        history
+    }
+
+    pub fn save(&self) {
+        let mut content = String::new();
+        for (date, score) in &self.entries {
+            content.push_str(&format!("{}={}\n", date, score));
+        }
+        fs::write(HISTORY_FILE, content).ok();
+    }
+
+    pub fn get_score(&self, date: &str) -> Option<u32> {
+        self.entries.iter().find(|(day, _)| day == date).map(|(_, score)| *score)
+    }
+
+    /// Enters the score of the day, replacing the one it had.
+    pub fn record(&mut self, date: &str, score: u32) {
+        if let Some((_, entry)) = self.entries.iter_mut().find(|(day, _)| day == date) {
+            *entry = score;
+            return;
+        }
+        let index = self.entries.partition_point(|(day, _)| day.as_str() > date);
+        self.entries.insert(index, (date.to_string(), score));
+    }
+
+    pub fn entries(&self) -> &[(String, u32)] {
+        &self.entries
+    }
+
+    /// Best score over all days, 0 before the first challenge.
+    pub fn best(&self) -> u32 {
+        self.entries.iter().map(|(_, score)| *score).max().unwrap_or(0)
+    }
+}
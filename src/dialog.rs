@@ -0,0 +1,77 @@
+/// Modal window asking the player to pick one of a few choices.
+///
+/// Open it with `WindowManager::open_dialog`, which returns a `DialogHandle`
+/// to poll for the picked value. Back picks the last choice, so the safe one
+/// should be added last.
+pub struct Dialog<T> {
+    title: String,
+    /// Lines are separated by `\n`
+    message: String,
+    choices: Vec<(String, T)>,
+}
+
+impl<T> Dialog<T> {
+    pub fn new(title: &str, message: &str) -> Self {
+        Dialog {
+            title: title.to_string(),
+            message: message.to_string(),
+            choices: vec![],
+        }
+    }
+
+    pub fn choice(mut self, label: &str, value: T) -> Self {
+        self.choices.push((label.to_string(), value));
+        self
+    }
+
+    /// Splits the dialog into what `WindowManager` draws and the values the handle keeps.
+    pub fn open(self, id: u64) -> (OpenDialog, DialogHandle<T>) {
+        let (labels, values) = self.choices.into_iter().unzip();
+        let dialog = OpenDialog {
+            id,
+            title: self.title,
+            message: self.message,
+            labels,
+        };
+        (dialog, DialogHandle { id, values })
+    }
+}
+
+impl Dialog<bool> {
+    /// Yes/no question answered with `true` for `yes`.
+    pub fn confirm(title: &str, message: &str, yes: &str, no: &str) -> Self {
+        Dialog::new(title, message)
+            .choice(yes, true)
+            .choice(no, false)
+    }
+}
+
+/// Identifies an open dialog and maps its answer back to the caller's type.
+pub struct DialogHandle<T> {
+    id: u64,
+    values: Vec<T>,
+}
+
+impl<T: Clone> DialogHandle<T> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn value(&self, choice: usize) -> Option<T> {
+        self.values.get(choice).cloned()
+    }
+}
+
+/// A dialog on the `WindowManager`'s stack.
+pub struct OpenDialog {
+    pub id: u64,
+    pub title: String,
+    pub message: String,
+    pub labels: Vec<String>,
+}
+
+impl OpenDialog {
+    pub fn window_name(&self) -> String {
+        format!("dialog_{}", self.id)
+    }
+}
@@ -0,0 +1,80 @@
+use super::game_mode::Tuning;
+
+/// Seconds after which half of what happened is forgotten
+const MEMORY_HALF_LIFE: f32 = 20.0;
+const MIN_PRESSURE: f32 = 0.6;
+const MAX_PRESSURE: f32 = 1.4;
+/// How fast the pressure follows its target, per second
+const ADJUST_RATE: f32 = 0.05;
+/// Accuracy the player is expected to have, better aim raises the pressure
+const EXPECTED_ACCURACY: f32 = 0.4;
+
+/// Adjusts the pacing of a run to how the players are doing.
+///
+/// Recent deaths, accuracy and damage taken are kept as counts that fade
+/// over time. They set a pressure the tuning is scaled with: struggling
+/// players see fewer and slower enemies that hurt less, players who do well
+/// get more of them.
+#[derive(Clone, Debug)]
+pub struct Director {
+    deaths: f32,
+    shots: f32,
+    hits: f32,
+    damage_taken: f32,
+    /// Scale of the pacing, 1.0 keeps the tuning as it is
+    pressure: f32,
+}
+
+impl Director {
+    pub fn new() -> Self {
+        Director {
+            deaths: 0.0,
+            shots: 0.0,
+            hits: 0.0,
+            damage_taken: 0.0,
+            pressure: 1.0,
+        }
+    }
+
+    pub fn record_shot(&mut self) {
+        self.shots += 1.0;
+    }
+
+    pub fn record_hit(&mut self) {
+        self.hits += 1.0;
+    }
+
+    pub fn record_damage(&mut self, damage: u32) {
+        self.damage_taken += damage as f32;
+    }
+
+    pub fn record_death(&mut self) {
+        self.deaths += 1.0;
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let fade = 0.5f32.powf(delta_time / MEMORY_HALF_LIFE);
+        for count in [&mut self.deaths, &mut self.shots, &mut self.hits, &mut self.damage_taken] {
+            *count *= fade;
+        }
+        let accuracy = if self.shots >= 1.0 {
+            self.hits / self.shots
+        } else {
+            EXPECTED_ACCURACY
+        };
+        let target = (1.0 + (accuracy - EXPECTED_ACCURACY) * 0.5 - self.deaths * 0.2 - self.damage_taken * 0.05)
+            .clamp(MIN_PRESSURE, MAX_PRESSURE);
+        let step = ADJUST_RATE * delta_time;
+        self.pressure += (target - self.pressure).clamp(-step, step);
+    }
+
+    /// The tuning scaled by the current pressure.
+    pub fn adjust(&self, tuning: &Tuning) -> Tuning {
+        Tuning {
+            spawn_chance: tuning.spawn_chance * self.pressure,
+            enemy_speed: tuning.enemy_speed * self.pressure.sqrt(),
+            enemy_damage: ((tuning.enemy_damage as f32 * self.pressure).round() as u32).max(1),
+            ..*tuning
+        }
+    }
+}
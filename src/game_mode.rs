@@ -0,0 +1,199 @@
+use std::fmt;
+use std::str::FromStr;
+use macroquad::math::{vec2, Vec2};
+
+/// Pixels per second a ship flies, its shots fly twice as fast
+pub const SHIP_SPEED: f32 = 200.0;
+pub const SHIP_SIZE: Vec2 = vec2(64.0, 96.0);
+pub const BULLET_SIZE: Vec2 = vec2(32.0, 32.0);
+/// Enemies pass through a respawned ship for this long
+pub const RESPAWN_INVULNERABILITY_SECONDS: f32 = 2.0;
+
+/// Numbers a run is played with, set up by its mode and adjusted by the
+/// difficulty and mutators.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tuning {
+    /// Lives each player brings into the run
+    pub lives: u32,
+    /// Health of a ship, a life is lost when it runs out
+    pub ship_health: u32,
+    /// Health an enemy flying into a ship takes
+    pub enemy_damage: u32,
+    /// Hits it takes to shoot an enemy down
+    pub enemy_health: u32,
+    /// Chance of a new enemy every step
+    pub spawn_chance: f32,
+    /// Chance of a new asteroid every step
+    pub asteroid_chance: f32,
+    /// Multiplies the speed enemies spawn with
+    pub enemy_speed: f32,
+    /// Multiplies the size enemies spawn with
+    pub enemy_size: f32,
+    /// Seconds between two shots
+    pub reload_time: f32,
+    /// Multiplies the points scored
+    pub score_multiplier: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            lives: 3,
+            ship_health: 2,
+            enemy_damage: 2,
+            enemy_health: 1,
+            spawn_chance: 0.09,
+            asteroid_chance: 0.003,
+            enemy_speed: 1.0,
+            enemy_size: 1.0,
+            reload_time: 0.1,
+            score_multiplier: 1.0,
+        }
+    }
+}
+
+impl Tuning {
+    /// The given points scaled by the score multiplier.
+    pub fn points(&self, points: u32) -> u32 {
+        (points as f32 * self.score_multiplier).round() as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("Unknown difficulty: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn apply(self, tuning: &mut Tuning) {
+        match self {
+            Difficulty::Easy => {
+                tuning.spawn_chance *= 0.75;
+                tuning.enemy_speed *= 0.8;
+                // Ships take a hit for free on every life
+                tuning.enemy_damage = 1;
+                // Runs of all difficulties share the high score tables
+                tuning.score_multiplier *= 0.5;
+            }
+            Difficulty::Normal => {}
+            Difficulty::Hard => {
+                tuning.spawn_chance *= 1.3;
+                tuning.enemy_speed *= 1.2;
+                tuning.enemy_damage *= 2;
+                tuning.enemy_health += 1;
+                tuning.score_multiplier *= 1.5;
+            }
+        }
+    }
+}
+
+/// Rules of a run, every mode keeps its own high score table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    /// Enemies keep coming until the lives run out
+    Endless,
+    /// As many points as possible before the clock runs out
+    TimeAttack,
+    /// No shooting, points come from staying alive
+    Survival,
+    /// Endless with a single life
+    Hardcore,
+    /// Endless on the seed and mutators of the day, see `DailyChallenge`
+    Daily,
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "endless" => Ok(GameMode::Endless),
+            "time_attack" => Ok(GameMode::TimeAttack),
+            "survival" => Ok(GameMode::Survival),
+            "hardcore" => Ok(GameMode::Hardcore),
+            "daily" => Ok(GameMode::Daily),
+            _ => Err(format!("Unknown game mode: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GameMode::Endless => "endless",
+            GameMode::TimeAttack => "time_attack",
+            GameMode::Survival => "survival",
+            GameMode::Hardcore => "hardcore",
+            GameMode::Daily => "daily",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Survival,
+        GameMode::Hardcore,
+        GameMode::Daily,
+    ];
+
+    pub fn tuning(self) -> Tuning {
+        match self {
+            GameMode::Hardcore => Tuning {
+                lives: 1,
+                ..Default::default()
+            },
+            _ => Tuning::default(),
+        }
+    }
+
+    /// Length of the run in seconds, `None` when it only ends with the last life.
+    pub fn time_limit(self) -> Option<f32> {
+        match self {
+            GameMode::TimeAttack => Some(180.0),
+            _ => None,
+        }
+    }
+
+    pub fn can_shoot(self) -> bool {
+        self != GameMode::Survival
+    }
+
+    /// Points every player still flying earns per second survived.
+    pub fn points_per_second(self) -> u32 {
+        match self {
+            GameMode::Survival => 10,
+            _ => 0,
+        }
+    }
+}
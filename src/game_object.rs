@@ -1,5 +1,6 @@
 use macroquad::prelude::*;
-use macroquad::experimental::animation::AnimatedSprite;
+use super::animation::AnimatedSprite;
+use super::animator::{AnimationEvent, Animator};
 use super::resource_manager::ResourceManager;
 
 pub struct GameObject {
@@ -8,10 +9,13 @@ pub struct GameObject {
     pub speed: f32,
     pub x: f32,
     pub y: f32,
+    /// Radians clockwise around the center
+    pub rotation: f32,
     pub collided: bool,
     pub texture_id: String,
     pub sprite: AnimatedSprite,
-    pub animation_num: usize,
+    /// Drives `sprite` when set, otherwise it loops its current animation
+    pub animator: Option<Animator>,
 }
 
 impl GameObject {
@@ -28,27 +32,37 @@ impl GameObject {
         }
     }
 
-    pub fn draw(&mut self, resource_manager: &ResourceManager) {
-        self.sprite.set_animation(self.animation_num);
-        self.sprite.update();
+    /// Advances the sprite, returns the animator's events.
+    pub fn animate(&mut self, delta_time: f32) -> Vec<AnimationEvent> {
+        match &mut self.animator {
+            Some(animator) => animator.update(&mut self.sprite, delta_time),
+            None => {
+                self.sprite.update(delta_time);
+                vec![]
+            }
+        }
+    }
+
+    pub fn draw(&self, resource_manager: &ResourceManager) {
+        self.draw_tinted(resource_manager, WHITE);
+    }
+
+    /// Draws the sprite multiplied by `tint`, its alpha makes the object translucent.
+    pub fn draw_tinted(&self, resource_manager: &ResourceManager, tint: Color) {
         let frame = self.sprite.frame();
         let texture = resource_manager.get_texture(&self.texture_id).unwrap();
         draw_texture_ex(
             texture,
             self.x - self.width / 2.0,
-            self.y - self.width / 2.0,
-            WHITE,
+            self.y - self.height / 2.0,
+            tint,
             DrawTextureParams {
                 dest_size: Some(vec2(self.width, self.height)),
                 source: Some(frame.source_rect),
+                rotation: self.rotation,
                 ..Default::default()
             }
         );
     }
-
-    pub fn set_animation_num(&mut self, num: usize) {
-        self.animation_num = num;
-    }
-
 }
 
@@ -0,0 +1,161 @@
+use std::fs;
+use macroquad::prelude::*;
+use super::game_mode::GameMode;
+
+const GHOSTS_DIR: &str = "ghosts";
+/// Ghosts kept on disk, the least recently saved ones are removed first
+const MAX_GHOSTS: usize = 20;
+
+/// Input of one simulation step and what came of it.
+#[derive(Clone, Copy, Debug)]
+pub struct GhostFrame {
+    /// Movement per axis scaled to a byte, analog sticks keep most of their precision
+    movement: (i8, i8),
+    pub fire: bool,
+    /// Velocity the ship was still being knocked away with by an asteroid
+    pub knockback: Vec2,
+    /// The ship was hit during the step and respawned, or went out on the last one
+    pub hit: bool,
+    pub score: u32,
+}
+
+impl GhostFrame {
+    pub fn new(movement: Vec2, fire: bool, knockback: Vec2, hit: bool, score: u32) -> Self {
+        let axis = |value: f32| (value.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8;
+        GhostFrame {
+            movement: (axis(movement.x), axis(movement.y)),
+            fire,
+            knockback,
+            hit,
+            score,
+        }
+    }
+
+    pub fn movement(&self) -> Vec2 {
+        vec2(self.movement.0 as f32, self.movement.1 as f32) / i8::MAX as f32
+    }
+}
+
+/// Inputs of a solo run, step by step, so it can be played back as a ghost
+/// next to later runs of the same mode and seed.
+///
+/// Only the best run of each mode and seed is kept, in `GHOSTS_DIR` as `key=value`
+/// lines with one `frame` line per step. Of those, only the `MAX_GHOSTS` most
+/// recently saved stay around.
+#[derive(Clone, Debug)]
+pub struct GhostRun {
+    pub mode: GameMode,
+    pub seed: u64,
+    pub frames: Vec<GhostFrame>,
+}
+
+impl GhostRun {
+    pub fn new(mode: GameMode, seed: u64) -> Self {
+        GhostRun {
+            mode,
+            seed,
+            frames: vec![],
+        }
+    }
+
+    pub fn load(mode: GameMode, seed: u64) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(mode, seed)).ok()?;
+        let mut run = GhostRun::new(mode, seed);
+        for line in content.lines() {
+            let Some(("frame", value)) = line.split_once('=') else {
+                continue;
+            };
+            let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+            // Runs saved before knockback was recorded have none
+            let (x, y, fire, hit, score, knockback) = match fields[..] {
+                [x, y, fire, hit, score] => (x, y, fire, hit, score, Ok(Vec2::ZERO)),
+                [x, y, fire, hit, score, knockback_x, knockback_y] => {
+                    let knockback = knockback_x.parse().and_then(|kx| Ok(vec2(kx, knockback_y.parse()?)));
+                    (x, y, fire, hit, score, knockback)
+                }
+                _ => continue,
+            };
+            let (Ok(x), Ok(y), Ok(score), Ok(knockback)) = (x.parse(), y.parse(), score.parse(), knockback) else {
+                continue;
+            };
+            run.frames.push(GhostFrame {
+                movement: (x, y),
+                fire: fire == "1",
+                knockback,
+                hit: hit == "1",
+                score,
+            });
+        }
+        (!run.frames.is_empty()).then_some(run)
+    }
+
+    /// Stores the run unless there already is one with a higher score.
+    pub fn save_if_best(&self) {
+        if GhostRun::load(self.mode, self.seed).is_some_and(|best| best.score() >= self.score()) {
+            return;
+        }
+        let mut content = format!("mode={}\n", self.mode);
+        content.push_str(&format!("seed={}\n", self.seed));
+        content.push_str(&format!("score={}\n", self.score()));
+        for frame in &self.frames {
+            content.push_str(&format!(
+                "frame={},{},{},{},{},{},{}\n",
+                frame.movement.0,
+                frame.movement.1,
+                frame.fire as u8,
+                frame.hit as u8,
+                frame.score,
+                frame.knockback.x,
+                frame.knockback.y
+            ));
+        }
+        if let Err(err) = fs::create_dir_all(GHOSTS_DIR).and_then(|_| fs::write(Self::path(self.mode, self.seed), content)) {
+            warn!("Couldn't save ghost: {}", err);
+            return;
+        }
+        Self::prune();
+    }
+
+    /// Removes all but the `MAX_GHOSTS` most recently saved ghosts.
+    fn prune() {
+        let Ok(entries) = fs::read_dir(GHOSTS_DIR) else {
+            return;
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                Some((modified, entry.path()))
+            })
+            .filter(|(_, path)| path.extension().is_some_and(|extension| extension == "dat"))
+            .collect();
+        files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        for (_, path) in files.into_iter().skip(MAX_GHOSTS) {
+            if let Err(err) = fs::remove_file(&path) {
+                warn!("Couldn't remove ghost {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    pub fn record(&mut self, frame: GhostFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn get_frame(&self, index: usize) -> Option<&GhostFrame> {
+        self.frames.get(index)
+    }
+
+    /// Final score of the run.
+    pub fn score(&self) -> u32 {
+        self.frames.last().map_or(0, |frame| frame.score)
+    }
+
+    /// Score after `steps` steps, the final one once the run is over.
+    pub fn score_at(&self, steps: usize) -> u32 {
+        self.frames[..steps.min(self.frames.len())].last().map_or(0, |frame| frame.score)
+    }
+
+    fn path(mode: GameMode, seed: u64) -> String {
+        format!("{}/{}-{}.dat", GHOSTS_DIR, mode, seed)
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::fs;
+use super::game_mode::GameMode;
+
+const HIGH_SCORES_FILE: &str = "highscores.dat";
+/// Single score saved before there were game modes, taken over into the endless table
+const LEGACY_HIGH_SCORE_FILE: &str = "highscore.dat";
+/// Scores kept per mode
+pub const TABLE_SIZE: usize = 5;
+
+/// Best scores of each game mode, highest first.
+///
+/// Stored in `HIGH_SCORES_FILE` as one `mode=score,score,...` line per mode.
+#[derive(Clone, Debug, Default)]
+pub struct HighScores {
+    tables: HashMap<GameMode, Vec<u32>>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let mut high_scores = HighScores::default();
+        let Ok(content) = fs::read_to_string(HIGH_SCORES_FILE) else {
+            if let Some(score) = fs::read_to_string(LEGACY_HIGH_SCORE_FILE)
+                .ok()
+                .and_then(|content| content.trim().parse().ok())
+            {
+                high_scores.insert(GameMode::Endless, score);
+            }
+            return high_scores;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let Ok(mode) = key.trim().parse() else {
+                continue;
+            };
+            for score in value.split(',').filter_map(|score| score.trim().parse().ok()) {
+                high_scores.insert(mode, score);
+            }
+        }
+        high_scores
+    }
+
+    pub fn save(&self) {
+        let mut content = String::new();
+        // Daily challenges keep their own history
+        for mode in GameMode::ALL.into_iter().filter(|mode| *mode != GameMode::Daily) {
+            let scores: Vec<String> = self.table(mode).iter().map(u32::to_string).collect();
+            content.push_str(&format!("{}={}\n", mode, scores.join(",")));
+        }
+        fs::write(HIGH_SCORES_FILE, content).ok();
+    }
+
+    pub fn table(&self, mode: GameMode) -> &[u32] {
+        self.tables.get(&mode).map_or(&[], Vec::as_slice)
+    }
+
+    /// Top score of the mode, 0 while its table is empty.
+    pub fn best(&self, mode: GameMode) -> u32 {
+        self.table(mode).first().copied().unwrap_or(0)
+    }
+
+    /// Place the score would take in the table, `None` if it doesn't make it.
+    pub fn rank(&self, mode: GameMode, score: u32) -> Option<usize> {
+        let table = self.table(mode);
+        let rank = table.iter().position(|entry| score > *entry).unwrap_or(table.len());
+        (score > 0 && rank < TABLE_SIZE).then_some(rank)
+    }
+
+    /// Score that falls out of the full table when the given one is entered.
+    pub fn displaced(&self, mode: GameMode, score: u32) -> Option<u32> {
+        let table = self.table(mode);
+        self.rank(mode, score)
+            .filter(|_| table.len() >= TABLE_SIZE)
+            .and_then(|_| table.last().copied())
+    }
+
+    /// Enters the score, returns its place or `None` if it didn't make the table.
+    pub fn insert(&mut self, mode: GameMode, score: u32) -> Option<usize> {
+        let rank = self.rank(mode, score)?;
+        let table = self.tables.entry(mode).or_default();
+        table.insert(rank, score);
+        table.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+}
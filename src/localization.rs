@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use super::resource_manager::{self as resource_manager, ResourceManager};
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+#[derive(DeJson)]
+struct StringTableFile {
+    name: String,
+    #[nserde(default)]
+    font: String,
+    #[nserde(default)]
+    font_scale: f32,
+    #[nserde(default)]
+    plural_rule: String,
+    strings: HashMap<String, String>,
+}
+
+/// Picks which plural form of a string is used for a count, the forms are
+/// stored under the string's key with a `.one`, `.few`, `.many` or `.other` suffix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluralRule {
+    /// "1 point", "2 points"
+    OneOther,
+    /// Russian, Ukrainian, Belarusian: "1 очко", "2 очка", "5 очков"
+    EastSlavic,
+}
+
+impl PluralRule {
+    fn category(&self, count: u32) -> &'static str {
+        match self {
+            PluralRule::OneOther => if count == 1 { "one" } else { "other" },
+            PluralRule::EastSlavic => match (count % 10, count % 100) {
+                (1, n) if n != 11 => "one",
+                (2..=4, n) if !(12..=14).contains(&n) => "few",
+                _ => "many",
+            },
+        }
+    }
+}
+
+/// Translated strings of one language.
+pub struct StringTable {
+    pub name: String,
+    /// Font used instead of the bundled one for scripts it doesn't cover
+    pub font: Option<String>,
+    /// Size multiplier for UI text in `font`
+    pub font_scale: f32,
+    plural_rule: PluralRule,
+    strings: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file = StringTableFile::deserialize_json(json).map_err(|err| err.to_string())?;
+        let plural_rule = match file.plural_rule.as_str() {
+            "" | "one_other" => PluralRule::OneOther,
+            "east_slavic" => PluralRule::EastSlavic,
+            rule => return Err(format!("Unknown plural rule: {}", rule)),
+        };
+        Ok(StringTable {
+            name: file.name,
+            font: Some(file.font).filter(|font| !font.is_empty()),
+            font_scale: if file.font_scale > 0.0 { file.font_scale } else { 1.0 },
+            plural_rule,
+            strings: file.strings,
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+}
+
+/// Looks up user-facing text in the current language, falling back to
+/// `DEFAULT_LANGUAGE` and then to the key itself for missing strings.
+///
+/// Strings can contain `{name}` placeholders that are filled in by `format`
+/// and `plural`.
+pub struct Localization<'a> {
+    resource_manager: &'a ResourceManager,
+    language: String,
+}
+
+impl<'a> Localization<'a> {
+    pub fn new(resource_manager: &'a ResourceManager, language: &str) -> Self {
+        let mut localization = Localization {
+            resource_manager,
+            language: DEFAULT_LANGUAGE.to_string(),
+        };
+        localization.set_language(language);
+        localization
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Returns `false` and keeps the current language if there is no table for `language`.
+    pub fn set_language(&mut self, language: &str) -> bool {
+        if self.resource_manager.get_string_table(language).is_none() {
+            warn!("Unknown language {}", language);
+            return false;
+        }
+        self.language = language.to_string();
+        true
+    }
+
+    /// Codes and display names of the available languages.
+    pub fn languages(&self) -> Vec<(&'static str, &'a str)> {
+        resource_manager::constants::LANGUAGES
+            .iter()
+            .filter_map(|code| {
+                self.resource_manager.get_string_table(code).map(|table| (*code, table.name.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn get<'b>(&'b self, key: &'b str) -> &'b str {
+        self.lookup(key).unwrap_or(key)
+    }
+
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        fill(self.get(key), args)
+    }
+
+    /// Formats the plural form of `key` for `count`, which is also available as `{count}`.
+    pub fn plural(&self, key: &str, count: u32, args: &[(&str, &dyn Display)]) -> String {
+        let category = self.table().plural_rule.category(count);
+        let text = self.lookup(&format!("{}.{}", key, category))
+            .or_else(|| self.lookup(&format!("{}.other", key)))
+            .unwrap_or(key);
+        let mut args = args.to_vec();
+        args.push(("count", &count));
+        fill(text, &args)
+    }
+
+    pub fn font_scale(&self) -> f32 {
+        self.table().font_scale
+    }
+
+    /// Id of the font that replaces the bundled one for the current language, if any.
+    pub fn fallback_font(&self) -> Option<&'a str> {
+        self.table().font.as_deref()
+    }
+
+    fn table(&self) -> &'a StringTable {
+        self.resource_manager.get_string_table(&self.language)
+            .or_else(|| self.resource_manager.get_string_table(DEFAULT_LANGUAGE))
+            .expect("Default language is not loaded.")
+    }
+
+    fn lookup(&self, key: &str) -> Option<&'a str> {
+        self.table().get(key).or_else(|| {
+            self.resource_manager.get_string_table(DEFAULT_LANGUAGE)?.get(key)
+        })
+    }
+}
+
+fn fill(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = text.to_string();
+    for (name, value) in args {
+        result = result.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    result
+}
@@ -0,0 +1,445 @@
+use std::ops::Range;
+use macroquad::prelude::*;
+use macroquad::ui::{widgets, Skin, Ui};
+use super::navigation::{NavAction, Navigation};
+
+/// Height of the large buttons of a menu
+pub const BUTTON_HEIGHT: f32 = 80.0;
+/// Height of the compact rows used by toggles, sliders and dropdowns
+pub const ROW_HEIGHT: f32 = 36.0;
+const SPACING: f32 = 6.0;
+/// Share of a row's width taken by its label
+const LABEL_WIDTH: f32 = 0.55;
+const ARROW_WIDTH: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Vertical,
+    Horizontal,
+}
+
+/// Hands out consecutive slices of an area along one axis, the other axis is filled.
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    area: Rect,
+    direction: Direction,
+    spacing: f32,
+    /// Offset of the next item along `direction`
+    cursor: f32,
+}
+
+impl Layout {
+    pub fn vertical(area: Rect, spacing: f32) -> Self {
+        Layout {
+            area,
+            direction: Direction::Vertical,
+            spacing,
+            cursor: 0.0,
+        }
+    }
+
+    pub fn horizontal(area: Rect, spacing: f32) -> Self {
+        Layout {
+            direction: Direction::Horizontal,
+            ..Layout::vertical(area, spacing)
+        }
+    }
+
+    /// Takes the next `extent` units, height for vertical layouts and width for horizontal ones.
+    pub fn next(&mut self, extent: f32) -> Rect {
+        let offset = self.cursor;
+        self.cursor += extent + self.spacing;
+        match self.direction {
+            Direction::Vertical => Rect::new(self.area.x, self.area.y + offset, self.area.w, extent),
+            Direction::Horizontal => Rect::new(self.area.x + offset, self.area.y, extent, self.area.h),
+        }
+    }
+
+    /// Takes whatever is left.
+    pub fn rest(&mut self) -> Rect {
+        let extent = match self.direction {
+            Direction::Vertical => self.area.h - self.cursor,
+            Direction::Horizontal => self.area.w - self.cursor,
+        };
+        self.next(extent.max(0.0))
+    }
+}
+
+/// Focus of a menu, kept between frames by `WindowManager`.
+#[derive(Clone, Debug, Default)]
+pub struct MenuState {
+    focused: usize,
+    open_dropdown: Option<OpenDropdown>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct OpenDropdown {
+    /// Focus slot of the dropdown
+    index: usize,
+    highlighted: usize,
+    /// Set once an option was picked, the dropdown applies it on the next frame
+    chosen: Option<usize>,
+}
+
+struct DropdownPopup {
+    area: Rect,
+    options: Vec<String>,
+}
+
+/// Immediate mode menu drawn into a UI window.
+///
+/// Every widget takes one focus slot in the order it's added, the focused
+/// one is highlighted and reacts to the navigation actions. The mouse works
+/// too, clicking a widget also focuses it.
+pub struct Menu<'a> {
+    ui: &'a mut Ui,
+    compact_skin: &'a Skin,
+    navigation: &'a Navigation,
+    state: &'a mut MenuState,
+    layout: Layout,
+    /// Focus slot of the next widget
+    next_index: usize,
+    popup: Option<DropdownPopup>,
+    /// Disabled menus are drawn but ignore the mouse and navigation, e.g. below a dialog
+    enabled: bool,
+    /// A text field is focused, Backspace edits it instead of going back
+    editing: bool,
+}
+
+impl<'a> Menu<'a> {
+    pub fn new(
+        ui: &'a mut Ui,
+        compact_skin: &'a Skin,
+        navigation: &'a Navigation,
+        state: &'a mut MenuState,
+        layout: Layout
+    ) -> Self {
+        Menu {
+            ui,
+            compact_skin,
+            navigation,
+            state,
+            layout,
+            next_index: 0,
+            popup: None,
+            enabled: true,
+            editing: false,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn ui(&mut self) -> &mut Ui {
+        self.ui
+    }
+
+    /// Back was pressed and didn't just close an open dropdown.
+    pub fn back(&self) -> bool {
+        self.state.open_dropdown.is_none()
+            && self.pressed(NavAction::Back)
+            && !(self.editing && is_key_pressed(KeyCode::Backspace))
+    }
+
+    pub fn button(&mut self, label: &str) -> bool {
+        let (index, focused) = self.slot();
+        let rect = self.layout.next(BUTTON_HEIGHT);
+        let clicked = widgets::Button::new(label)
+            .position(rect.point())
+            .size(rect.size())
+            .selected(focused)
+            .ui(self.ui);
+        self.click(index, clicked) || self.action(focused, NavAction::Confirm)
+    }
+
+    /// Button as high as a compact row, for less important entries.
+    pub fn small_button(&mut self, label: &str) -> bool {
+        let (index, focused) = self.slot();
+        let rect = self.layout.next(ROW_HEIGHT);
+        let clicked = self.compact(|ui| {
+            widgets::Button::new(label)
+                .position(rect.point())
+                .size(rect.size())
+                .selected(focused)
+                .ui(ui)
+        });
+        self.click(index, clicked) || self.action(focused, NavAction::Confirm)
+    }
+
+    /// Row of text that can't be focused, `value` is shown where other rows have their controls.
+    pub fn info(&mut self, label: &str, value: &str) {
+        let area = self.layout.next(ROW_HEIGHT);
+        let mut layout = Layout::horizontal(area, SPACING);
+        let label_area = layout.next(area.w * LABEL_WIDTH);
+        let value_area = layout.rest();
+        self.compact(|ui| {
+            widgets::Label::new(label)
+                .position(label_area.point() + vec2(SPACING * 2.0, 0.0))
+                .ui(ui);
+            widgets::Label::new(value)
+                .position(value_area.point())
+                .ui(ui);
+        });
+    }
+
+    /// On/off row, returns `true` when the value changed.
+    pub fn toggle(&mut self, label: &str, value: &mut bool) -> bool {
+        let (index, focused) = self.slot();
+        let mut row = self.row(label, focused);
+        let clicked = self.click(index, row.1);
+        let value_area = row.0.rest();
+        self.compact(|ui| {
+            widgets::Label::new(if *value { "[x]" } else { "[ ]" })
+                .position(value_area.point())
+                .ui(ui);
+        });
+        let changed = clicked
+            || self.action(focused, NavAction::Confirm)
+            || self.action(focused, NavAction::Left)
+            || self.action(focused, NavAction::Right);
+        if changed {
+            *value = !*value;
+        }
+        changed
+    }
+
+    /// Row with arrows to step the value through `range`, returns `true` when the value changed.
+    pub fn slider(&mut self, label: &str, value: &mut f32, range: Range<f32>, step: f32) -> bool {
+        let (index, focused) = self.slot();
+        let (mut layout, clicked) = self.row(label, focused);
+        self.click(index, clicked);
+        let (decrease_area, value_area, increase_area) = (
+            layout.next(ARROW_WIDTH),
+            layout.next(ARROW_WIDTH * 2.0),
+            layout.next(ARROW_WIDTH)
+        );
+        let (decrease, increase) = self.compact(|ui| {
+            let decrease = widgets::Button::new("<")
+                .position(decrease_area.point())
+                .size(decrease_area.size())
+                .ui(ui);
+            widgets::Label::new(format!("{:.0}%", (*value - range.start) / (range.end - range.start) * 100.0))
+                .position(value_area.point())
+                .ui(ui);
+            let increase = widgets::Button::new(">")
+                .position(increase_area.point())
+                .size(increase_area.size())
+                .ui(ui);
+            (decrease, increase)
+        });
+        let (decrease, increase) = (self.click(index, decrease), self.click(index, increase));
+        let delta = if decrease || self.action(focused, NavAction::Left) {
+            -step
+        } else if increase || self.action(focused, NavAction::Right) {
+            step
+        } else {
+            return false;
+        };
+        let new_value = (*value + delta).clamp(range.start, range.end);
+        let changed = new_value != *value;
+        *value = new_value;
+        changed
+    }
+
+    /// Row with text typed in while it's focused, only characters `accept`
+    /// lets through are taken. Returns `true` when the text changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn text_field(&mut self, label: &str, value: &mut String, accept: impl Fn(char) -> bool) -> bool {
+        let (index, focused) = self.slot();
+        let (mut layout, clicked) = self.row(label, focused);
+        self.click(index, clicked);
+        let value_area = layout.rest();
+        let editing = focused && self.enabled;
+        self.compact(|ui| {
+            widgets::Label::new(if editing { format!("{}_", value) } else { value.clone() })
+                .position(value_area.point())
+                .ui(ui);
+        });
+
+        // Characters typed elsewhere are dropped, the queue is newest first
+        let mut typed = vec![];
+        while let Some(character) = get_char_pressed() {
+            typed.push(character);
+        }
+        if !editing {
+            return false;
+        }
+        self.editing = true;
+        let previous = value.clone();
+        value.extend(typed.into_iter().rev().filter(|character| accept(*character)));
+        if is_key_pressed(KeyCode::Backspace) {
+            value.pop();
+        }
+        *value != previous
+    }
+
+    /// Row showing the selected option, confirming opens the list of all
+    /// options and left/right cycle through them. Returns `true` when the selection changed.
+    pub fn dropdown(&mut self, label: &str, options: &[&str], selected: &mut usize) -> bool {
+        let (index, focused) = self.slot();
+        let (mut layout, clicked) = self.row(label, focused);
+        let value_area = layout.rest();
+        let value_clicked = self.compact(|ui| {
+            widgets::Button::new(options.get(*selected).copied().unwrap_or(""))
+                .position(value_area.point())
+                .size(value_area.size())
+                .ui(ui)
+        });
+        if options.is_empty() {
+            return false;
+        }
+
+        if let Some(open) = self.state.open_dropdown
+            && open.index == index
+        {
+            if let Some(chosen) = open.chosen {
+                self.state.open_dropdown = None;
+                let changed = chosen != *selected;
+                *selected = chosen.min(options.len() - 1);
+                return changed;
+            }
+            self.popup = Some(DropdownPopup {
+                area: value_area,
+                options: options.iter().map(|option| option.to_string()).collect(),
+            });
+            return false;
+        }
+
+        if self.click(index, clicked || value_clicked) || self.action(focused, NavAction::Confirm) {
+            self.state.open_dropdown = Some(OpenDropdown {
+                index,
+                highlighted: *selected,
+                chosen: None,
+            });
+            return false;
+        }
+        let previous = *selected;
+        if self.action(focused, NavAction::Left) {
+            *selected = (*selected + options.len() - 1) % options.len();
+        } else if self.action(focused, NavAction::Right) {
+            *selected = (*selected + 1) % options.len();
+        }
+        previous != *selected
+    }
+
+    /// Moves the focus and draws the open dropdown on top, call after adding all widgets.
+    pub fn finish(mut self) {
+        let count = self.next_index;
+        if let Some(popup) = self.popup.take() {
+            self.draw_popup(popup);
+        } else if let Some(open) = self.state.open_dropdown {
+            // The dropdown isn't part of the menu anymore
+            if open.index >= count {
+                self.state.open_dropdown = None;
+            }
+        } else if count > 0 {
+            if self.pressed(NavAction::Up) {
+                self.state.focused = (self.state.focused + count - 1) % count;
+            }
+            if self.pressed(NavAction::Down) {
+                self.state.focused = (self.state.focused + 1) % count;
+            }
+        }
+        self.state.focused = self.state.focused.min(count.saturating_sub(1));
+    }
+
+    fn draw_popup(&mut self, popup: DropdownPopup) {
+        let (up, down, confirm) = (
+            self.pressed(NavAction::Up),
+            self.pressed(NavAction::Down),
+            self.pressed(NavAction::Confirm)
+        );
+        let Some(open) = &mut self.state.open_dropdown else {
+            return;
+        };
+        let count = popup.options.len();
+        if up {
+            open.highlighted = (open.highlighted + count - 1) % count;
+        }
+        if down {
+            open.highlighted = (open.highlighted + 1) % count;
+        }
+        let highlighted = open.highlighted;
+
+        let mut layout = Layout::vertical(
+            Rect::new(popup.area.x, popup.area.bottom(), popup.area.w, count as f32 * ROW_HEIGHT),
+            0.0
+        );
+        let mut clicked = None;
+        self.compact(|ui| {
+            for (option_index, option) in popup.options.iter().enumerate() {
+                let rect = layout.next(ROW_HEIGHT);
+                if widgets::Button::new(option.as_str())
+                    .position(rect.point())
+                    .size(rect.size())
+                    .selected(option_index == highlighted)
+                    .ui(ui)
+                {
+                    clicked = Some(option_index);
+                }
+            }
+        });
+
+        if self.pressed(NavAction::Back) {
+            self.state.open_dropdown = None;
+        } else if let Some(open) = &mut self.state.open_dropdown {
+            if confirm {
+                open.chosen = Some(highlighted);
+            }
+            if clicked.is_some() && self.enabled {
+                open.chosen = clicked;
+            }
+        }
+    }
+
+    /// Draws the background and label of a compact row, returns the layout
+    /// for the rest of it and whether the row itself was clicked.
+    fn row(&mut self, label: &str, focused: bool) -> (Layout, bool) {
+        let area = self.layout.next(ROW_HEIGHT);
+        let mut layout = Layout::horizontal(area, SPACING);
+        let label_area = layout.next(area.w * LABEL_WIDTH);
+        let clicked = self.compact(|ui| {
+            let clicked = widgets::Button::new("")
+                .position(area.point())
+                .size(area.size())
+                .selected(focused)
+                .ui(ui);
+            widgets::Label::new(label)
+                .position(label_area.point() + vec2(SPACING * 2.0, 0.0))
+                .ui(ui);
+            clicked
+        });
+        (layout, clicked)
+    }
+
+    fn compact<T>(&mut self, f: impl FnOnce(&mut Ui) -> T) -> T {
+        self.ui.push_skin(self.compact_skin);
+        let result = f(self.ui);
+        self.ui.pop_skin();
+        result
+    }
+
+    fn slot(&mut self) -> (usize, bool) {
+        let index = self.next_index;
+        self.next_index += 1;
+        (index, index == self.state.focused && self.state.open_dropdown.is_none())
+    }
+
+    /// Focuses the widget when it was clicked, ignored while a dropdown is open or the menu is disabled.
+    fn click(&mut self, index: usize, clicked: bool) -> bool {
+        if clicked && self.enabled && self.state.open_dropdown.is_none() {
+            self.state.focused = index;
+            return true;
+        }
+        false
+    }
+
+    fn action(&self, focused: bool, action: NavAction) -> bool {
+        focused && self.pressed(action)
+    }
+
+    fn pressed(&self, action: NavAction) -> bool {
+        self.enabled && self.navigation.pressed(action)
+    }
+}
@@ -0,0 +1,183 @@
+use std::f32::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+use macroquad::prelude::*;
+
+/// Enemy speed paths are timed for, faster enemies get through them quicker
+const PATH_REFERENCE_SPEED: f32 = 350.0;
+/// Share of its speed a circling enemy sinks with
+const CIRCLE_SINK: f32 = 0.4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// Starts and ends on the first and last point, pulled towards the others
+    Bezier,
+    /// Passes through every point
+    CatmullRom,
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bezier" => Ok(Curve::Bezier),
+            "catmull_rom" => Ok(Curve::CatmullRom),
+            _ => Err(format!("Unknown curve: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Curve::Bezier => "bezier",
+            Curve::CatmullRom => "catmull_rom",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Curve {
+    /// Point at `t` in `0..=1` along the curve, `points` must not be empty.
+    pub fn point(self, points: &[Vec2], t: f32) -> Vec2 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Curve::Bezier => {
+                // De Casteljau, blend neighbours until one point is left
+                let mut points = points.to_vec();
+                while points.len() > 1 {
+                    points = points.windows(2).map(|pair| pair[0].lerp(pair[1], t)).collect();
+                }
+                points[0]
+            }
+            Curve::CatmullRom => {
+                let last = points.len() - 1;
+                if last == 0 {
+                    return points[0];
+                }
+                let position = t * last as f32;
+                let segment = (position.floor() as usize).min(last - 1);
+                let u = position - segment as f32;
+                // The end points are doubled to steer into the first and last segment
+                let p0 = points[segment.saturating_sub(1)];
+                let p1 = points[segment];
+                let p2 = points[segment + 1];
+                let p3 = points[(segment + 2).min(last)];
+                0.5 * (2.0 * p1
+                    + (p2 - p0) * u
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * u * u
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * u * u * u)
+            }
+        }
+    }
+}
+
+/// How an enemy flies after it spawned.
+///
+/// Positions are offsets from the spawn point after some time in flight, so
+/// enemies sharing a movement and spawn time keep their places in a formation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Movement {
+    /// Straight down
+    Straight,
+    /// Down while swaying from side to side `frequency` times a second
+    Sine { amplitude: f32, frequency: f32 },
+    /// Down while changing direction every half `period`
+    Zigzag { amplitude: f32, period: f32 },
+    /// Along a curve through the playfield, straight down once it's done.
+    /// Points are fractions of the playfield size.
+    Path { curve: Curve, points: Vec<Vec2>, duration: f32 },
+    /// Loops around a center that sinks slowly
+    Circle { radius: f32, angular_speed: f32 },
+}
+
+impl Movement {
+    /// Where enemies flying it spawn, `None` when it can start anywhere above the playfield.
+    pub fn start(&self, playfield: Vec2) -> Option<Vec2> {
+        match self {
+            Movement::Path { points, .. } => points.first().map(|point| *point * playfield),
+            _ => None,
+        }
+    }
+
+    /// Offset from the spawn point after `time` seconds at `speed` pixels per second.
+    pub fn offset(&self, time: f32, speed: f32, playfield: Vec2) -> Vec2 {
+        match self {
+            Movement::Straight => vec2(0.0, speed * time),
+            Movement::Sine { amplitude, frequency } => {
+                vec2(amplitude * (TAU * frequency * time).sin(), speed * time)
+            }
+            Movement::Zigzag { amplitude, period } => {
+                // Triangle wave starting in the middle, heading right
+                let phase = (time / period + 0.75).fract();
+                vec2(amplitude * (4.0 * (phase - 0.5).abs() - 1.0), speed * time)
+            }
+            Movement::Path { curve, points, duration } => {
+                let path_time = duration * PATH_REFERENCE_SPEED / speed;
+                let offset = curve.point(points, time / path_time) * playfield - points[0] * playfield;
+                offset + vec2(0.0, speed * (time - path_time).max(0.0))
+            }
+            Movement::Circle { radius, angular_speed } => {
+                let angle = angular_speed * time;
+                vec2(radius * angle.sin(), speed * CIRCLE_SINK * time + radius * (1.0 - angle.cos()))
+            }
+        }
+    }
+}
+
+/// Arrangement of a group of enemies that fly as a unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Formation {
+    /// Side by side
+    Line,
+    /// Behind each other
+    Column,
+    /// A leader with the others trailing off to both sides
+    Vee,
+}
+
+impl FromStr for Formation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "line" => Ok(Formation::Line),
+            "column" => Ok(Formation::Column),
+            "vee" => Ok(Formation::Vee),
+            _ => Err(format!("Unknown formation: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Formation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Formation::Line => "line",
+            Formation::Column => "column",
+            Formation::Vee => "vee",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Formation {
+    /// Offsets of `count` places `spacing` apart, relative to the leader.
+    /// Places further back are further up, off the top of the playfield.
+    pub fn slots(self, count: u32, spacing: f32) -> Vec<Vec2> {
+        (0..count)
+            .map(|index| {
+                let index = index as f32;
+                match self {
+                    Formation::Line => vec2((index - (count - 1) as f32 / 2.0) * spacing, 0.0),
+                    Formation::Column => vec2(0.0, -index * spacing),
+                    Formation::Vee => {
+                        let rank = ((index + 1.0) / 2.0).floor();
+                        let side = if index as u32 % 2 == 1 { -1.0 } else { 1.0 };
+                        vec2(side * rank * spacing, -rank * spacing * 0.8)
+                    }
+                }
+            })
+            .collect()
+    }
+}
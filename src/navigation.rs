@@ -0,0 +1,172 @@
+use macroquad::prelude::*;
+
+/// Stick deflection that counts as a direction press
+#[cfg(not(target_arch = "wasm32"))]
+const STICK_PRESS_THRESHOLD: f32 = 0.5;
+/// The stick has to come back below this before it can press again
+#[cfg(not(target_arch = "wasm32"))]
+const STICK_RELEASE_THRESHOLD: f32 = 0.3;
+/// Smaller stick deflections don't move a player
+#[cfg(not(target_arch = "wasm32"))]
+const STICK_DEADZONE: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+/// Gameplay input of one gamepad.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GamepadState {
+    /// Left stick or D-pad, y points down like the playfield
+    pub movement: Vec2,
+    /// The fire button was pressed this frame
+    pub fire: bool,
+}
+
+/// Menu navigation from the keyboard and, on desktop, connected gamepads.
+///
+/// Call `update` once per frame, `pressed` then reports the actions
+/// triggered during that frame.
+pub struct Navigation {
+    pressed: Vec<NavAction>,
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepads: Option<gilrs::Gilrs>,
+    /// Direction the left stick is held in per axis, -1, 0 or 1
+    #[cfg(not(target_arch = "wasm32"))]
+    stick: (i8, i8),
+    /// Gamepads whose fire button was pressed this frame
+    #[cfg(not(target_arch = "wasm32"))]
+    fire_pressed: Vec<gilrs::GamepadId>,
+}
+
+impl Navigation {
+    pub fn new() -> Self {
+        Navigation {
+            pressed: vec![],
+            #[cfg(not(target_arch = "wasm32"))]
+            gamepads: gilrs::Gilrs::new()
+                .map_err(|err| warn!("Gamepads are not available: {}", err))
+                .ok(),
+            #[cfg(not(target_arch = "wasm32"))]
+            stick: (0, 0),
+            #[cfg(not(target_arch = "wasm32"))]
+            fire_pressed: vec![],
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.pressed.clear();
+        for (action, keys) in [
+            (NavAction::Up, [KeyCode::Up, KeyCode::W]),
+            (NavAction::Down, [KeyCode::Down, KeyCode::S]),
+            (NavAction::Left, [KeyCode::Left, KeyCode::A]),
+            (NavAction::Right, [KeyCode::Right, KeyCode::D]),
+            (NavAction::Confirm, [KeyCode::Enter, KeyCode::Space]),
+            (NavAction::Back, [KeyCode::Escape, KeyCode::Backspace]),
+        ] {
+            if keys.iter().any(|key| is_key_pressed(*key)) {
+                self.pressed.push(action);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.poll_gamepads();
+    }
+
+    pub fn pressed(&self, action: NavAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Gameplay input of the `slot`th connected gamepad, if there is one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn gamepad(&self, slot: usize) -> Option<GamepadState> {
+        use gilrs::{Axis, Button};
+
+        let (id, gamepad) = self.gamepads.as_ref()?.gamepads().nth(slot)?;
+        let button = |button| if gamepad.is_pressed(button) { 1.0 } else { 0.0 };
+        let stick = vec2(gamepad.value(Axis::LeftStickX), -gamepad.value(Axis::LeftStickY));
+        let movement = if stick.length() >= STICK_DEADZONE {
+            stick
+        } else {
+            vec2(
+                button(Button::DPadRight) - button(Button::DPadLeft),
+                button(Button::DPadDown) - button(Button::DPadUp)
+            )
+        };
+        Some(GamepadState {
+            movement,
+            fire: self.fire_pressed.contains(&id),
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn gamepad(&self, _slot: usize) -> Option<GamepadState> {
+        None
+    }
+
+    /// Drops this frame's actions once something, like a dialog, has handled them.
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll_gamepads(&mut self) {
+        use gilrs::{Axis, Button, EventType};
+
+        self.fire_pressed.clear();
+        let Some(gamepads) = &mut self.gamepads else {
+            return;
+        };
+        while let Some(event) = gamepads.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if button == Button::South {
+                        self.fire_pressed.push(event.id);
+                    }
+                    let action = match button {
+                        Button::DPadUp => NavAction::Up,
+                        Button::DPadDown => NavAction::Down,
+                        Button::DPadLeft => NavAction::Left,
+                        Button::DPadRight => NavAction::Right,
+                        Button::South | Button::Start => NavAction::Confirm,
+                        Button::East | Button::Select => NavAction::Back,
+                        _ => continue,
+                    };
+                    self.pressed.push(action);
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    if let Some(direction) = stick_press(&mut self.stick.0, value) {
+                        self.pressed.push(if direction > 0 { NavAction::Right } else { NavAction::Left });
+                    }
+                }
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => {
+                    // Up is positive on the stick
+                    if let Some(direction) = stick_press(&mut self.stick.1, value) {
+                        self.pressed.push(if direction > 0 { NavAction::Up } else { NavAction::Down });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Tracks a stick axis with some hysteresis, returns the direction when it's newly pushed.
+#[cfg(not(target_arch = "wasm32"))]
+fn stick_press(held: &mut i8, value: f32) -> Option<i8> {
+    if value.abs() < STICK_RELEASE_THRESHOLD {
+        *held = 0;
+        return None;
+    }
+    let direction = value.signum() as i8;
+    if value.abs() >= STICK_PRESS_THRESHOLD && *held != direction {
+        *held = direction;
+        return Some(direction);
+    }
+    None
+}
@@ -0,0 +1,321 @@
+use std::fmt;
+use std::str::FromStr;
+use macroquad::prelude::*;
+use super::game_mode::{Tuning, BULLET_SIZE, RESPAWN_INVULNERABILITY_SECONDS, SHIP_SIZE, SHIP_SPEED};
+use super::rng::Rng;
+
+/// Steps per second, both peers simulate at this rate no matter their frame rate
+pub const TICK_RATE: f32 = 60.0;
+pub const FRAME_TIME: f32 = 1.0 / TICK_RATE;
+/// Both peers play on the same field, independent of their window size
+pub const WORLD_SIZE: Vec2 = vec2(800.0, 600.0);
+const BULLET_SPEED: f32 = SHIP_SPEED * 2.0;
+const RESPAWN_INVULNERABILITY_FRAMES: u32 = (RESPAWN_INVULNERABILITY_SECONDS * TICK_RATE) as u32;
+/// Points for shooting the other ship in versus
+const VERSUS_HIT_POINTS: u32 = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetMode {
+    /// Both players fight the enemies together
+    Coop,
+    /// The ships can shoot each other, the last one flying wins
+    Versus,
+}
+
+impl FromStr for NetMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "coop" => Ok(NetMode::Coop),
+            "versus" => Ok(NetMode::Versus),
+            _ => Err(format!("Unknown online mode: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for NetMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NetMode::Coop => "coop",
+            NetMode::Versus => "versus",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl NetMode {
+    pub const ALL: [NetMode; 2] = [NetMode::Coop, NetMode::Versus];
+}
+
+/// Buttons held by one player during one frame, packed into a byte for the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NetInput(u8);
+
+impl NetInput {
+    const UP: u8 = 1;
+    const DOWN: u8 = 1 << 1;
+    const LEFT: u8 = 1 << 2;
+    const RIGHT: u8 = 1 << 3;
+    const FIRE: u8 = 1 << 4;
+
+    /// Analog movement is rounded to the eight directions.
+    pub fn new(movement: Vec2, fire: bool) -> Self {
+        let mut bits = 0;
+        for (held, bit) in [
+            (movement.y <= -0.5, Self::UP),
+            (movement.y >= 0.5, Self::DOWN),
+            (movement.x <= -0.5, Self::LEFT),
+            (movement.x >= 0.5, Self::RIGHT),
+            (fire, Self::FIRE),
+        ] {
+            if held {
+                bits |= bit;
+            }
+        }
+        NetInput(bits)
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        NetInput(bits)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn movement(self) -> Vec2 {
+        let axis = |negative, positive| {
+            (self.0 & positive != 0) as i32 as f32 - (self.0 & negative != 0) as i32 as f32
+        };
+        vec2(axis(Self::LEFT, Self::RIGHT), axis(Self::UP, Self::DOWN))
+    }
+
+    pub fn fire(self) -> bool {
+        self.0 & Self::FIRE != 0
+    }
+
+    /// Guess for a frame whose input hasn't arrived yet, the player keeps
+    /// steering the same way but doesn't press fire again.
+    pub fn predicted(self) -> Self {
+        NetInput(self.0 & !Self::FIRE)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetShip {
+    pub position: Vec2,
+    pub lives: u32,
+    pub score: u32,
+    /// Frames until the ship can fire again
+    reload: u32,
+    /// Frames left of the invulnerability after a respawn
+    pub invulnerable: u32,
+}
+
+impl NetShip {
+    pub fn is_out(&self) -> bool {
+        self.lives == 0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetBullet {
+    pub position: Vec2,
+    /// Index of the ship that fired it
+    pub owner: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetEnemy {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub speed: f32,
+    /// Index into the enemy sizes the world was created with
+    pub kind: usize,
+}
+
+/// Something the scene shows or plays a sound for.
+#[derive(Clone, Copy, Debug)]
+pub enum NetEvent {
+    Shot { position: Vec2 },
+    Kill { position: Vec2, size: f32 },
+    ShipHit { position: Vec2 },
+}
+
+/// Game state of an online match, advanced in fixed steps from the inputs of
+/// both players.
+///
+/// Online matches play by simpler rules than `PlayingScene`: ships, shots and
+/// enemies flying straight down, without waves, behaviours, asteroids, ship
+/// health, combos, difficulties or game modes. What the two have in common,
+/// ship speed and size, lives, reload, respawn invulnerability and spawn
+/// chance, comes from `game_mode` and the default `Tuning`, so changing it there
+/// changes both.
+///
+/// Everything random comes from the seeded `Rng`, so peers starting from the
+/// same seed and feeding the same inputs end up in the same state. Cloning it
+/// takes the snapshots rollback restores.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetWorld {
+    pub frame: u32,
+    pub mode: NetMode,
+    rng: Rng,
+    tuning: Tuning,
+    /// Base size of each enemy kind, scaled up when spawned
+    enemy_sizes: Vec<Vec2>,
+    pub ships: [NetShip; 2],
+    pub bullets: Vec<NetBullet>,
+    pub enemies: Vec<NetEnemy>,
+}
+
+impl NetWorld {
+    pub fn new(seed: u64, mode: NetMode, enemy_sizes: Vec<Vec2>) -> Self {
+        let tuning = Tuning::default();
+        let ship = |index| NetShip {
+            position: spawn_position(index),
+            lives: tuning.lives,
+            score: 0,
+            reload: 0,
+            invulnerable: 0,
+        };
+        NetWorld {
+            frame: 0,
+            mode,
+            rng: Rng::new(seed),
+            tuning,
+            enemy_sizes,
+            ships: [ship(0), ship(1)],
+            bullets: vec![],
+            enemies: vec![],
+        }
+    }
+
+    /// Co-op ends when both ships are out, versus as soon as one is.
+    pub fn is_over(&self) -> bool {
+        match self.mode {
+            NetMode::Coop => self.ships.iter().all(NetShip::is_out),
+            NetMode::Versus => self.ships.iter().any(NetShip::is_out),
+        }
+    }
+
+    /// The ship still flying at the end of a versus match, `None` on a draw.
+    pub fn winner(&self) -> Option<usize> {
+        match self.ships.iter().filter(|ship| !ship.is_out()).count() {
+            1 => self.ships.iter().position(|ship| !ship.is_out()),
+            _ => None,
+        }
+    }
+
+    pub fn step(&mut self, inputs: [NetInput; 2]) -> Vec<NetEvent> {
+        let mut events = vec![];
+        self.frame += 1;
+
+        for (index, (ship, input)) in self.ships.iter_mut().zip(inputs).enumerate() {
+            if ship.is_out() {
+                continue;
+            }
+            ship.reload = ship.reload.saturating_sub(1);
+            ship.invulnerable = ship.invulnerable.saturating_sub(1);
+            ship.position += input.movement() * SHIP_SPEED * FRAME_TIME;
+            ship.position = ship.position.clamp(SHIP_SIZE, WORLD_SIZE - SHIP_SIZE);
+            if input.fire() && ship.reload == 0 {
+                let position = ship.position - vec2(0.0, 24.0);
+                self.bullets.push(NetBullet {
+                    position,
+                    owner: index,
+                });
+                ship.reload = (self.tuning.reload_time * TICK_RATE).round() as u32;
+                events.push(NetEvent::Shot { position });
+            }
+        }
+
+        if self.rng.chance(self.tuning.spawn_chance) && !self.enemy_sizes.is_empty() {
+            let kind = self.rng.gen_index(self.enemy_sizes.len());
+            let size = self.enemy_sizes[kind] * self.rng.gen_range(3.0, 5.0);
+            let speed = self.rng.gen_range(300.0, 400.0);
+            let x = self.rng.gen_range(size.x / 2.0, WORLD_SIZE.x - size.x / 2.0);
+            self.enemies.push(NetEnemy {
+                position: vec2(x, -size.y),
+                size,
+                speed,
+                kind,
+            });
+        }
+        for enemy in &mut self.enemies {
+            enemy.position.y += enemy.speed * FRAME_TIME;
+        }
+        for bullet in &mut self.bullets {
+            bullet.position.y -= BULLET_SPEED * FRAME_TIME;
+        }
+
+        for index in 0..self.ships.len() {
+            let ship = &self.ships[index];
+            if ship.is_out() || ship.invulnerable > 0 {
+                continue;
+            }
+            let ship_rect = centered_rect(ship.position, SHIP_SIZE);
+            if let Some(enemy) = self.enemies
+                .iter()
+                .position(|enemy| ship_rect.overlaps(&centered_rect(enemy.position, enemy.size)))
+            {
+                self.enemies.remove(enemy);
+                events.push(self.hit_ship(index));
+            } else if self.mode == NetMode::Versus
+                && let Some(bullet) = self.bullets
+                    .iter()
+                    .position(|bullet| {
+                        bullet.owner != index && ship_rect.overlaps(&centered_rect(bullet.position, BULLET_SIZE))
+                    })
+            {
+                let owner = self.bullets.remove(bullet).owner;
+                self.ships[owner].score += VERSUS_HIT_POINTS;
+                events.push(self.hit_ship(index));
+            }
+        }
+
+        let mut hit_bullets = vec![false; self.bullets.len()];
+        let (bullets, ships) = (&self.bullets, &mut self.ships);
+        self.enemies.retain(|enemy| {
+            let enemy_rect = centered_rect(enemy.position, enemy.size);
+            let Some(bullet) = (0..bullets.len()).find(|&bullet| {
+                !hit_bullets[bullet] && enemy_rect.overlaps(&centered_rect(bullets[bullet].position, BULLET_SIZE))
+            }) else {
+                return true;
+            };
+            hit_bullets[bullet] = true;
+            ships[bullets[bullet].owner].score += enemy.size.y.round() as u32;
+            events.push(NetEvent::Kill {
+                position: enemy.position,
+                size: enemy.size.y,
+            });
+            false
+        });
+        let mut hit_bullets = hit_bullets.into_iter();
+        self.bullets.retain(|_| !hit_bullets.next().unwrap_or(false));
+
+        self.enemies.retain(|enemy| enemy.position.y < WORLD_SIZE.y + enemy.size.y);
+        self.bullets.retain(|bullet| bullet.position.y > -BULLET_SIZE.y);
+        events
+    }
+
+    /// Takes a life, the ship respawns while it has some left.
+    fn hit_ship(&mut self, index: usize) -> NetEvent {
+        let ship = &mut self.ships[index];
+        let position = ship.position;
+        ship.lives -= 1;
+        if !ship.is_out() {
+            ship.position = spawn_position(index);
+            ship.invulnerable = RESPAWN_INVULNERABILITY_FRAMES;
+        }
+        NetEvent::ShipHit { position }
+    }
+}
+
+fn spawn_position(index: usize) -> Vec2 {
+    vec2(WORLD_SIZE.x * (index + 1) as f32 / 3.0, WORLD_SIZE.y / 2.0)
+}
+
+fn centered_rect(center: Vec2, size: Vec2) -> Rect {
+    Rect::new(center.x - size.x / 2.0, center.y - size.y / 2.0, size.x, size.y)
+}
@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use super::net_world::{NetEvent, NetInput, NetMode, NetWorld};
+use super::rng::Rng;
+
+pub const DEFAULT_PORT: u16 = 7777;
+/// A peer runs ahead of the last input it has from the other one by at most this many frames
+const MAX_PREDICTION_FRAMES: usize = 8;
+/// Inputs are sent again with every packet until acknowledged, up to this many
+const MAX_INPUTS_PER_PACKET: usize = 32;
+/// The peer is gone when nothing arrived for this long
+const TIMEOUT_SECONDS: f64 = 5.0;
+const MAX_PACKET_SIZE: usize = 512;
+/// Starts every packet, anything else arriving on the port is ignored
+const PROTOCOL_MAGIC: &[u8; 4] = b"DGN1";
+
+/// Network trouble faked on outgoing packets, for testing over loopback.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinkConditions {
+    /// One-way delay in seconds
+    pub latency: f64,
+    /// Random extra delay of up to this many seconds, reorders packets
+    pub jitter: f64,
+    /// Share of packets dropped, 0.0 to 1.0
+    pub loss: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Sent by the joining peer until the host answers
+    Hello,
+    /// The host's answer with everything needed to start the same match
+    Welcome { seed: u64, mode: NetMode },
+    /// The sender's inputs from `first_frame` on, `ack` is how many of the
+    /// receiver's inputs the sender has
+    Input { ack: u32, first_frame: u32, inputs: Vec<NetInput> },
+    Quit,
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = PROTOCOL_MAGIC.to_vec();
+        match self {
+            Message::Hello => bytes.push(0),
+            Message::Welcome { seed, mode } => {
+                bytes.push(1);
+                bytes.extend_from_slice(&seed.to_le_bytes());
+                bytes.push(NetMode::ALL.iter().position(|other| other == mode).unwrap_or(0) as u8);
+            }
+            Message::Input { ack, first_frame, inputs } => {
+                bytes.push(2);
+                bytes.extend_from_slice(&ack.to_le_bytes());
+                bytes.extend_from_slice(&first_frame.to_le_bytes());
+                bytes.push(inputs.len() as u8);
+                bytes.extend(inputs.iter().map(|input| input.bits()));
+            }
+            Message::Quit => bytes.push(3),
+        }
+        bytes
+    }
+
+    /// `None` for anything that isn't a well-formed message.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let body = bytes.strip_prefix(PROTOCOL_MAGIC)?;
+        let (tag, body) = body.split_first()?;
+        let u32_at = |offset: usize| Some(u32::from_le_bytes(body.get(offset..offset + 4)?.try_into().ok()?));
+        match tag {
+            0 => Some(Message::Hello),
+            1 => Some(Message::Welcome {
+                seed: u64::from_le_bytes(body.get(0..8)?.try_into().ok()?),
+                mode: *NetMode::ALL.get(*body.get(8)? as usize)?,
+            }),
+            2 => {
+                let count = *body.get(8)? as usize;
+                Some(Message::Input {
+                    ack: u32_at(0)?,
+                    first_frame: u32_at(4)?,
+                    inputs: body.get(9..9 + count)?.iter().map(|bits| NetInput::from_bits(*bits)).collect(),
+                })
+            }
+            3 => Some(Message::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Non-blocking UDP link to a single peer.
+///
+/// The host binds a known port and talks to whoever says `Hello` first, the
+/// joining side knows the host's address from the start.
+pub struct Connection {
+    socket: UdpSocket,
+    peer: Option<SocketAddr>,
+    conditions: LinkConditions,
+    /// Decides which packets are lost and how late they are
+    rng: Rng,
+    /// Packets held back by the simulated latency, with the time they go out
+    delayed: Vec<(f64, Vec<u8>)>,
+    last_received: Option<f64>,
+}
+
+impl Connection {
+    pub fn host(port: u16, conditions: LinkConditions) -> io::Result<Self> {
+        Connection::new(UdpSocket::bind(("0.0.0.0", port))?, None, conditions)
+    }
+
+    pub fn join(address: &str, conditions: LinkConditions) -> io::Result<Self> {
+        let peer = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("No address for {}", address)))?;
+        let local = if peer.is_ipv4() { "0.0.0.0" } else { "::" };
+        Connection::new(UdpSocket::bind((local, 0))?, Some(peer), conditions)
+    }
+
+    fn new(socket: UdpSocket, peer: Option<SocketAddr>, conditions: LinkConditions) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Connection {
+            socket,
+            peer,
+            conditions,
+            rng: Rng::new(macroquad::miniquad::date::now().to_bits()),
+            delayed: vec![],
+            last_received: None,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message, now: f64) {
+        if self.peer.is_none() || self.rng.chance(self.conditions.loss) {
+            return;
+        }
+        let jitter = self.rng.gen_range(0.0, 1.0) as f64 * self.conditions.jitter;
+        self.delayed.push((now + self.conditions.latency + jitter, message.encode()));
+        self.flush(now);
+    }
+
+    /// Sends the packets whose delay is over and returns what arrived since the last call.
+    pub fn receive(&mut self, now: f64) -> Vec<Message> {
+        self.flush(now);
+        let mut messages = vec![];
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        while let Ok((size, sender)) = self.socket.recv_from(&mut buffer) {
+            if self.peer.is_some_and(|peer| peer != sender) {
+                continue;
+            }
+            let Some(message) = Message::decode(&buffer[..size]) else {
+                continue;
+            };
+            if self.peer.is_none() {
+                if message != Message::Hello {
+                    continue;
+                }
+                self.peer = Some(sender);
+            }
+            self.last_received = Some(now);
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// Sends what the simulated latency still holds back, before the connection is dropped.
+    pub fn close(&mut self) {
+        self.flush(f64::INFINITY);
+    }
+
+    pub fn timed_out(&self, now: f64) -> bool {
+        self.last_received.is_some_and(|last_received| now - last_received > TIMEOUT_SECONDS)
+    }
+
+    fn flush(&mut self, now: f64) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+        let socket = &self.socket;
+        self.delayed.retain(|(due, bytes)| {
+            if *due > now {
+                return true;
+            }
+            if let Err(err) = socket.send_to(bytes, peer) {
+                macroquad::logging::warn!("Couldn't send packet: {}", err);
+            }
+            false
+        });
+    }
+}
+
+/// Keeps a `NetWorld` in step with a peer using rollback.
+///
+/// Frames run right away with a guess for the peer's input. Once the real
+/// input arrives and differs from the guess, the world is restored from the
+/// snapshot taken before that frame and simulated again up to the present.
+pub struct RollbackSession {
+    connection: Connection,
+    /// Index of the local player's ship, the host flies ship 0
+    local: usize,
+    seed: u64,
+    world: NetWorld,
+    /// World before each frame from `snapshot_frame` on, the oldest frames a rollback can go back to
+    snapshots: VecDeque<NetWorld>,
+    snapshot_frame: usize,
+    local_inputs: Vec<NetInput>,
+    remote_inputs: Vec<NetInput>,
+    /// Remote input every simulated frame ran with, guessed or real
+    used_remote_inputs: Vec<NetInput>,
+    /// Frames before this ran with the real remote input
+    verified_frames: usize,
+    /// Number of our inputs the peer has
+    acknowledged: usize,
+    rollbacks: u32,
+    disconnected: bool,
+}
+
+impl RollbackSession {
+    pub fn new(connection: Connection, local: usize, seed: u64, world: NetWorld) -> Self {
+        RollbackSession {
+            connection,
+            local,
+            seed,
+            world,
+            snapshots: VecDeque::new(),
+            snapshot_frame: 0,
+            local_inputs: vec![],
+            remote_inputs: vec![],
+            used_remote_inputs: vec![],
+            verified_frames: 0,
+            acknowledged: 0,
+            rollbacks: 0,
+            disconnected: false,
+        }
+    }
+
+    pub fn world(&self) -> &NetWorld {
+        &self.world
+    }
+
+    pub fn local_player(&self) -> usize {
+        self.local
+    }
+
+    /// Number of times a misprediction was corrected.
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    /// Every frame so far ran with the peer's real input, so the world is the same on both sides.
+    pub fn is_confirmed(&self) -> bool {
+        self.verified_frames >= self.world.frame as usize
+    }
+
+    /// Too far ahead of the peer to keep guessing.
+    pub fn is_waiting(&self) -> bool {
+        self.world.frame as usize >= self.remote_inputs.len() + MAX_PREDICTION_FRAMES
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    /// Runs the next frame with `input` unless the session has to wait for
+    /// the peer or the match is over. Returns the events of the new frame,
+    /// `None` when no frame ran.
+    pub fn advance(&mut self, input: NetInput, now: f64) -> Option<Vec<NetEvent>> {
+        self.poll(now);
+        self.roll_back_mispredictions();
+        let events = if self.disconnected || self.world.is_over() || self.is_waiting() {
+            None
+        } else {
+            // After a rollback cut the match short, frames already sent keep their input
+            if self.local_inputs.len() == self.world.frame as usize {
+                self.local_inputs.push(input);
+            }
+            Some(self.simulate_frame())
+        };
+        self.send_inputs(now);
+        self.drop_verified_snapshots();
+        events
+    }
+
+    /// Tells the peer we're leaving, a few times in case some get lost.
+    pub fn quit(&mut self, now: f64) {
+        for _ in 0..3 {
+            self.connection.send(&Message::Quit, now);
+        }
+        // The session is dropped right after, nothing may stay behind in the delay
+        self.connection.close();
+    }
+
+    fn poll(&mut self, now: f64) {
+        for message in self.connection.receive(now) {
+            match message {
+                // Our welcome got lost, the peer is still waiting for it
+                Message::Hello if self.local == 0 => {
+                    let welcome = Message::Welcome {
+                        seed: self.seed,
+                        mode: self.world.mode,
+                    };
+                    self.connection.send(&welcome, now);
+                }
+                Message::Input { ack, first_frame, inputs } => {
+                    self.acknowledged = self.acknowledged.max(ack as usize);
+                    for (frame, input) in (first_frame as usize..).zip(inputs) {
+                        if frame == self.remote_inputs.len() {
+                            self.remote_inputs.push(input);
+                        }
+                    }
+                }
+                Message::Quit => self.disconnected = true,
+                Message::Hello | Message::Welcome { .. } => {}
+            }
+        }
+        if self.connection.timed_out(now) {
+            self.disconnected = true;
+        }
+    }
+
+    fn roll_back_mispredictions(&mut self) {
+        let verifiable = (self.world.frame as usize).min(self.remote_inputs.len());
+        let mispredicted = (self.verified_frames..verifiable)
+            .find(|frame| self.used_remote_inputs[*frame] != self.remote_inputs[*frame]);
+        if let Some(frame) = mispredicted {
+            let present = self.world.frame as usize;
+            self.world = self.snapshots[frame - self.snapshot_frame].clone();
+            self.snapshots.truncate(frame - self.snapshot_frame);
+            self.used_remote_inputs.truncate(frame);
+            // Effects of the replayed frames were shown the first time around
+            while (self.world.frame as usize) < present && !self.world.is_over() {
+                self.simulate_frame();
+            }
+            self.rollbacks += 1;
+        }
+        self.verified_frames = verifiable;
+    }
+
+    /// Runs the frame after the current one, its local input has to be known.
+    fn simulate_frame(&mut self) -> Vec<NetEvent> {
+        let frame = self.world.frame as usize;
+        let remote = match self.remote_inputs.get(frame) {
+            Some(input) => *input,
+            None => self.remote_inputs.last().map_or(NetInput::default(), |input| input.predicted()),
+        };
+        let mut inputs = [remote; 2];
+        inputs[self.local] = self.local_inputs[frame];
+        self.snapshots.push_back(self.world.clone());
+        self.used_remote_inputs.push(remote);
+        self.world.step(inputs)
+    }
+
+    fn send_inputs(&mut self, now: f64) {
+        let first_frame = self.acknowledged.min(self.local_inputs.len());
+        let last_frame = self.local_inputs.len().min(first_frame + MAX_INPUTS_PER_PACKET);
+        let message = Message::Input {
+            ack: self.remote_inputs.len() as u32,
+            first_frame: first_frame as u32,
+            inputs: self.local_inputs[first_frame..last_frame].to_vec(),
+        };
+        self.connection.send(&message, now);
+    }
+
+    /// Frames that ran with the real input can't be rolled back anymore.
+    fn drop_verified_snapshots(&mut self) {
+        while self.snapshot_frame < self.verified_frames && !self.snapshots.is_empty() {
+            self.snapshots.pop_front();
+            self.snapshot_frame += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::*;
+    use crate::net_world::{FRAME_TIME, NetInput, NetMode, NetWorld};
+    use crate::rng::Rng;
+    use super::{Connection, LinkConditions, Message, RollbackSession};
+
+    const SEED: u64 = 42;
+    const FRAMES: usize = 300;
+
+    /// Input of the player with `index` on `frame`, changing often enough
+    /// that the peer's guesses are wrong now and then.
+    fn scripted_input(index: usize, frame: usize) -> NetInput {
+        let direction = (frame / 20 + index) % 4;
+        let movement = [vec2(1.0, 0.0), vec2(0.0, 1.0), vec2(-1.0, 0.0), vec2(0.0, -1.0)][direction];
+        NetInput::new(movement, (frame + index * 3).is_multiple_of(7))
+    }
+
+    /// Looks for the peer's inputs without running a frame.
+    fn settle(session: &mut RollbackSession, now: f64) {
+        session.poll(now);
+        session.roll_back_mispredictions();
+        session.send_inputs(now);
+        session.drop_verified_snapshots();
+    }
+
+    #[test]
+    fn rollback_over_bad_loopback_link_ends_in_sync() {
+        let conditions = LinkConditions {
+            latency: 0.05,
+            jitter: 0.03,
+            loss: 0.2,
+        };
+        let mut host = Connection::host(0, conditions).unwrap();
+        let port = host.socket.local_addr().unwrap().port();
+        let mut guest = Connection::join(&format!("127.0.0.1:{}", port), conditions).unwrap();
+        host.rng = Rng::new(1);
+        guest.rng = Rng::new(2);
+
+        let mut now = 0.0;
+        let mut connected = false;
+        for _ in 0..600 {
+            now += FRAME_TIME as f64;
+            guest.send(&Message::Hello, now);
+            if host.receive(now).contains(&Message::Hello) {
+                connected = true;
+                break;
+            }
+        }
+        assert!(connected, "The host never heard the guest");
+
+        let world = NetWorld::new(SEED, NetMode::Coop, vec![vec2(17.0, 16.0), vec2(32.0, 16.0)]);
+        let mut sessions = [
+            RollbackSession::new(host, 0, SEED, world.clone()),
+            RollbackSession::new(guest, 1, SEED, world),
+        ];
+        for _ in 0..FRAMES * 10 {
+            now += FRAME_TIME as f64;
+            for (index, session) in sessions.iter_mut().enumerate() {
+                let frame = session.world().frame as usize;
+                if frame < FRAMES && !session.world().is_over() {
+                    session.advance(scripted_input(index, frame), now);
+                } else {
+                    settle(session, now);
+                }
+            }
+            let done = sessions.iter().all(|session| {
+                session.is_confirmed() && (session.world().frame as usize >= FRAMES || session.world().is_over())
+            });
+            if done {
+                break;
+            }
+        }
+
+        for session in &sessions {
+            assert!(!session.is_disconnected());
+            assert!(session.is_confirmed(), "Stuck at frame {}", session.world().frame);
+            assert!(session.world().frame as usize >= FRAMES || session.world().is_over());
+        }
+        assert_eq!(sessions[0].world(), sessions[1].world());
+        assert!(sessions.iter().map(RollbackSession::rollbacks).sum::<u32>() > 0, "Nothing was rolled back");
+    }
+}
@@ -0,0 +1,186 @@
+use macroquad::prelude::*;
+use macroquad::Error;
+use super::settings::Settings;
+use super::viewport::Viewport;
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+varying lowp vec2 uv;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+}
+";
+
+pub const BLOOM: &str = "bloom";
+pub const CHROMATIC_ABERRATION: &str = "chromatic_aberration";
+pub const SCANLINES: &str = "scanlines";
+pub const CRT: &str = "crt";
+pub const VIGNETTE: &str = "vignette";
+
+struct PostPass {
+    name: String,
+    material: Material,
+    enabled: bool,
+}
+
+/// Renders the frame offscreen and runs it through a chain of fragment shaders.
+///
+/// Every pass samples the previous result from the `Texture` sampler and gets
+/// the target size in pixels as the `iResolution` uniform.
+pub struct PostProcessor {
+    passes: Vec<PostPass>,
+    targets: Vec<RenderTarget>,
+    size: (u32, u32),
+    active: bool,
+}
+
+impl PostProcessor {
+    pub fn new() -> Self {
+        let mut post_processor = PostProcessor {
+            passes: vec![],
+            targets: vec![],
+            size: (0, 0),
+            active: false,
+        };
+        for (name, shader) in [
+            (BLOOM, include_str!("bloom-shader.glsl")),
+            (CHROMATIC_ABERRATION, include_str!("chromatic-aberration-shader.glsl")),
+            (SCANLINES, include_str!("scanlines-shader.glsl")),
+            (CRT, include_str!("crt-shader.glsl")),
+            (VIGNETTE, include_str!("vignette-shader.glsl")),
+        ] {
+            post_processor.register(name, shader, false)
+                .expect("Couldn't compile post-processing shader.");
+        }
+        post_processor
+    }
+
+    /// Appends a pass to the end of the chain.
+    pub fn register(&mut self, name: &str, fragment_shader: &str, enabled: bool) -> Result<(), Error> {
+        let material = load_material(
+            ShaderSource::Glsl {
+                vertex: VERTEX_SHADER,
+                fragment: fragment_shader
+            },
+            MaterialParams {
+                uniforms: vec![UniformDesc::new("iResolution", UniformType::Float2)],
+                ..Default::default()
+            }
+        )?;
+        self.passes.push(PostPass {
+            name: name.to_string(),
+            material,
+            enabled,
+        });
+        Ok(())
+    }
+
+    pub fn pass_names(&self) -> impl Iterator<Item = &str> {
+        self.passes.iter().map(|pass| pass.name.as_str())
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.passes.iter().any(|pass| pass.name == name && pass.enabled)
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        if let Some(pass) = self.passes.iter_mut().find(|pass| pass.name == name) {
+            pass.enabled = enabled;
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        for pass in &mut self.passes {
+            pass.enabled = settings.post_effects.get(&pass.name).copied().unwrap_or(false);
+        }
+    }
+
+    /// Redirects drawing into the offscreen target when at least one pass is enabled.
+    pub fn begin(&mut self, viewport: &mut Viewport) {
+        self.active = self.passes.iter().any(|pass| pass.enabled);
+        if !self.active {
+            viewport.set_render_target(None);
+            set_default_camera();
+            return;
+        }
+
+        let size = (
+            (screen_width() * screen_dpi_scale()) as u32,
+            (screen_height() * screen_dpi_scale()) as u32
+        );
+        if size != self.size {
+            self.targets = (0..2)
+                .map(|_| {
+                    let target = render_target(size.0, size.1);
+                    target.texture.set_filter(FilterMode::Linear);
+                    target
+                })
+                .collect();
+            self.size = size;
+        }
+        viewport.set_render_target(Some(self.targets[0].clone()));
+        set_camera(&self.screen_camera());
+    }
+
+    /// Camera for drawing in window coordinates, replaces `set_default_camera`
+    /// while a frame is being post-processed.
+    pub fn screen_camera(&self) -> Camera2D {
+        Camera2D {
+            target: vec2(screen_width() / 2.0, screen_height() / 2.0),
+            zoom: vec2(2.0 / screen_width(), 2.0 / screen_height()),
+            render_target: self.targets.first().filter(|_| self.active).cloned(),
+            ..Default::default()
+        }
+    }
+
+    /// Runs the enabled passes and presents the result on the screen.
+    pub fn end(&mut self) {
+        if !self.active {
+            return;
+        }
+
+        let resolution = vec2(self.size.0 as f32, self.size.1 as f32);
+        let enabled: Vec<usize> = (0..self.passes.len())
+            .filter(|index| self.passes[*index].enabled)
+            .collect();
+        let mut source = 0;
+        for (step, index) in enabled.iter().enumerate() {
+            let last = step == enabled.len() - 1;
+            if last {
+                set_default_camera();
+            } else {
+                set_camera(&self.target_camera(1 - source));
+            }
+
+            let material = &self.passes[*index].material;
+            material.set_uniform("iResolution", resolution);
+            gl_use_material(material);
+            draw_texture_ex(
+                &self.targets[source].texture,
+                0.0,
+                0.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(screen_width(), screen_height())),
+                    ..Default::default()
+                }
+            );
+            gl_use_default_material();
+            source = 1 - source;
+        }
+    }
+
+    fn target_camera(&self, index: usize) -> Camera2D {
+        Camera2D {
+            render_target: Some(self.targets[index].clone()),
+            ..self.screen_camera()
+        }
+    }
+}
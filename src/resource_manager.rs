@@ -1,6 +1,13 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 use macroquad::prelude::*;
 use macroquad::audio::{load_sound, Sound};
+use super::sound_bank::SoundBank;
+use super::animation::{AnimatedSprite, SpriteSheet};
+use super::achievements::AchievementDefinition;
+use super::waves::WaveSet;
+use super::localization::StringTable;
+use super::theme::Theme;
 
 pub mod constants {
     pub const PLAYER_TEX_ID: &str = "player_texture";
@@ -10,162 +17,72 @@ pub mod constants {
     pub const ENEMY_MEDIUM_TEX_ID: &str = "enemy_medium_texture";
     pub const ENEMY_BIG_TEX_ID: &str = "enemy_big_texture";
     pub const ENEMY_TEXTURES: &[&str] = &[ENEMY_SMALL_TEX_ID, ENEMY_MEDIUM_TEX_ID, ENEMY_BIG_TEX_ID];
-
-    pub const WINDOW_BACKGROUND: &str = "window_background";
-    pub const BUTTON_BACKGROUND: &str = "button_background";
-    pub const BUTTON_PRESSED_BACKGROUND: &str = "button_pressed_background";
+    pub const ASTEROID_TEX_ID: &str = "asteroid_texture";
 
     pub const THEME_MUSIC: &str = "theme_music";
     pub const EXPLOSION_SOUND: &str = "explosion_sound";
     pub const LASER_SOUND: &str = "laser_sound";
 
     pub const FONT: &str = "font";
-}
-
-pub mod animations {
-    use macroquad::experimental::animation::{AnimatedSprite, Animation};
-
-    pub fn player_animation() -> AnimatedSprite {
-        AnimatedSprite::new(
-                16,
-                24,
-                &[
-                    Animation {
-                        name: "idle".to_string(),
-                        row: 0,
-                        frames: 2,
-                        fps: 12,
-                    },
-                    Animation {
-                        name: "slight-left".to_string(),
-                        row: 1,
-                        frames: 2,
-                        fps: 12,
-                    },
-                    Animation {
-                        name: "left".to_string(),
-                        row: 2,
-                        frames: 2,
-                        fps: 12,
-                    },
-                    Animation {
-                        name: "slight-right".to_string(),
-                        row: 3,
-                        frames: 2,
-                        fps: 12,
-                    },
-                    Animation {
-                        name: "right".to_string(),
-                        row: 4,
-                        frames: 2,
-                        fps: 12,
-                    }
-                ],
-                true
-            )
-    }
-
-    pub fn bullet_animation() -> AnimatedSprite {
-        AnimatedSprite::new(
-                16,
-                16,
-                &[
-                    Animation {
-                        name: "bullet".to_string(),
-                        row: 0,
-                        frames: 2,
-                        fps: 12
-                    },
-                    Animation {
-                        name: "bolt".to_string(),
-                        row: 1,
-                        frames: 2,
-                        fps: 12
-                    },
-                ],
-                true
-            )
-    }
-
-    pub fn enemy_small_animation() -> AnimatedSprite {
-        AnimatedSprite::new(
-                17,
-                16,
-                &[
-                    Animation {
-                        name: "enemy_small".to_string(),
-                        row: 0,
-                        frames: 2,
-                        fps: 12
-                    }
-                ],
-                true
-            )
-    }
-
-    pub fn enemy_medium_animation() -> AnimatedSprite {
-        AnimatedSprite::new(
-                32,
-                16,
-                &[
-                    Animation {
-                        name: "enemy_medium".to_string(),
-                        row: 0,
-                        frames: 2,
-                        fps: 12
-                    }
-                ],
-                true
-            )
-    }
-
-    pub fn enemy_big_animation() -> AnimatedSprite {
-        AnimatedSprite::new(
-                32,
-                32,
-                &[
-                    Animation {
-                        name: "enemy_big".to_string(),
-                        row: 0,
-                        frames: 2,
-                        fps: 12
-                    }
-                ],
-                true
-            )
-    }
+    pub const FALLBACK_FONT: &str = "fallback_font";
 
+    pub const LANGUAGES: &[&str] = &["en", "de", "ru"];
+    pub const THEMES: &[&str] = &["default", "high_contrast"];
 }
 
 pub struct ResourceManager {
     textures: HashMap<String, Texture2D>,
+    sprite_sheets: HashMap<String, Rc<SpriteSheet>>,
     images: HashMap<String, Image>,
     sounds: HashMap<String, Sound>,
+    sound_banks: HashMap<String, SoundBank>,
     fonts: HashMap<String, Vec<u8>>,
+    text_fonts: HashMap<String, Font>,
+    string_tables: HashMap<String, StringTable>,
+    themes: HashMap<String, Theme>,
+    achievements: Vec<AchievementDefinition>,
+    waves: WaveSet,
 }
 
 impl ResourceManager {
     pub fn new() -> Self {
         ResourceManager {
             textures: HashMap::new(),
+            sprite_sheets: HashMap::new(),
             images: HashMap::new(),
             sounds: HashMap::new(),
+            sound_banks: HashMap::new(),
             fonts: HashMap::new(),
+            text_fonts: HashMap::new(),
+            string_tables: HashMap::new(),
+            themes: HashMap::new(),
+            achievements: vec![],
+            waves: WaveSet::default(),
         }
     }
 
     pub async fn load_resources(&mut self) {
         set_pc_assets_folder("assets");
         self.load_textures().await;
-        self.load_images().await;
+        self.load_sprite_sheets().await;
         self.load_sounds().await;
         self.load_fonts().await;
+        self.load_string_tables().await;
+        self.load_themes().await;
+        self.load_achievements().await;
+        self.load_waves().await;
     }
 
     pub fn get_texture(&self, id: &str) -> Option<&Texture2D> {
         self.textures.get(id)
     }
 
+    /// New sprite animated with the sheet of the texture with the same id.
+    pub fn get_animated_sprite(&self, id: &str) -> Option<AnimatedSprite> {
+        self.sprite_sheets.get(id).map(|sheet| AnimatedSprite::new(sheet.clone()))
+    }
+
+    /// Images are loaded for the themes and keyed by their file name.
     pub fn get_image(&self, id: &str) -> Option<&Image> {
         self.images.get(id)
     }
@@ -174,10 +91,34 @@ impl ResourceManager {
         self.sounds.get(id)
     }
 
+    pub fn get_sound_bank(&self, id: &str) -> Option<&SoundBank> {
+        self.sound_banks.get(id)
+    }
+
     pub fn get_font(&self, id: &str) -> Option<&Vec<u8>> {
         self.fonts.get(id)
     }
 
+    pub fn get_text_font(&self, id: &str) -> Option<&Font> {
+        self.text_fonts.get(id)
+    }
+
+    pub fn get_string_table(&self, language: &str) -> Option<&StringTable> {
+        self.string_tables.get(language)
+    }
+
+    pub fn get_theme(&self, id: &str) -> Option<&Theme> {
+        self.themes.get(id)
+    }
+
+    pub fn get_achievements(&self) -> &[AchievementDefinition] {
+        &self.achievements
+    }
+
+    pub fn get_waves(&self) -> &WaveSet {
+        &self.waves
+    }
+
     async fn load_textures(&mut self) {
         let player_texture: Texture2D = load_texture("ship.png")
             .await
@@ -203,6 +144,10 @@ impl ResourceManager {
             .await
             .expect("Couldn't load texture file.");
         enemy_big_texture.set_filter(FilterMode::Nearest);
+        let asteroid_texture: Texture2D = load_texture("asteroid.png")
+            .await
+            .expect("Couldn't load texture file.");
+        asteroid_texture.set_filter(FilterMode::Nearest);
         build_textures_atlas();
 
         self.textures.insert(constants::PLAYER_TEX_ID.to_string(), player_texture);
@@ -211,22 +156,25 @@ impl ResourceManager {
         self.textures.insert(constants::ENEMY_SMALL_TEX_ID.to_string(), enemy_small_texture);
         self.textures.insert(constants::ENEMY_MEDIUM_TEX_ID.to_string(), enemy_medium_texture);
         self.textures.insert(constants::ENEMY_BIG_TEX_ID.to_string(), enemy_big_texture);
+        self.textures.insert(constants::ASTEROID_TEX_ID.to_string(), asteroid_texture);
     }
 
-    async fn load_images(&mut self) {
-        let window_background = load_image("window_background.png")
-            .await
-            .expect("Couldn't load image file.");
-        let button_background = load_image("button_background.png")
-            .await
-            .expect("Couldn't load image file.");
-        let button_pressed_background = load_image("button_clicked_background.png")
-            .await
-            .expect("Couldn't load image file.");
-
-        self.images.insert(constants::WINDOW_BACKGROUND.to_string(), window_background);
-        self.images.insert(constants::BUTTON_BACKGROUND.to_string(), button_background);
-        self.images.insert(constants::BUTTON_PRESSED_BACKGROUND.to_string(), button_pressed_background);
+    async fn load_sprite_sheets(&mut self) {
+        for (id, path) in [
+            (constants::PLAYER_TEX_ID, "animations/ship.json"),
+            (constants::BULLET_TEX_ID, "animations/laser-bolts.json"),
+            (constants::ENEMY_SMALL_TEX_ID, "animations/enemy-small.json"),
+            (constants::ENEMY_MEDIUM_TEX_ID, "animations/enemy-medium.json"),
+            (constants::ENEMY_BIG_TEX_ID, "animations/enemy-big.json"),
+            (constants::ASTEROID_TEX_ID, "animations/asteroid.json"),
+        ] {
+            let json = load_string(path)
+                .await
+                .expect("Couldn't load animation file.");
+            let sheet = SpriteSheet::from_aseprite_json(&json)
+                .unwrap_or_else(|err| panic!("Couldn't parse {}: {}", path, err));
+            self.sprite_sheets.insert(id.to_string(), Rc::new(sheet));
+        }
     }
 
     async fn load_sounds(&mut self) {
@@ -243,14 +191,77 @@ impl ResourceManager {
         self.sounds.insert(constants::THEME_MUSIC.to_string(), theme_music);
         self.sounds.insert(constants::EXPLOSION_SOUND.to_string(), explosion_sound);
         self.sounds.insert(constants::LASER_SOUND.to_string(), laser_sound);
+
+        let explosion_bank = SoundBank::from_wav(&load_file("explosion.wav").await.expect("Couldn't load file."))
+            .await
+            .expect("Couldn't build sound bank.");
+        let laser_bank = SoundBank::from_wav(&load_file("laser.wav").await.expect("Couldn't load file."))
+            .await
+            .expect("Couldn't build sound bank.");
+
+        self.sound_banks.insert(constants::EXPLOSION_SOUND.to_string(), explosion_bank);
+        self.sound_banks.insert(constants::LASER_SOUND.to_string(), laser_bank);
     }
 
     async fn load_fonts(&mut self) {
-        let font = load_file("atari_games.ttf")
+        for (id, path) in [
+            (constants::FONT, "atari_games.ttf"),
+            (constants::FALLBACK_FONT, "DejaVuSans.ttf"),
+        ] {
+            let font = load_file(path)
+                .await
+                .expect("Couldn't load file.");
+            let text_font = load_ttf_font_from_bytes(&font)
+                .expect("Couldn't load font.");
+            self.fonts.insert(id.to_string(), font);
+            self.text_fonts.insert(id.to_string(), text_font);
+        }
+    }
+
+    async fn load_string_tables(&mut self) {
+        for language in constants::LANGUAGES {
+            let json = load_string(&format!("lang/{}.json", language))
+                .await
+                .expect("Couldn't load string table.");
+            let table = StringTable::from_json(&json)
+                .unwrap_or_else(|err| panic!("Couldn't parse string table {}: {}", language, err));
+            self.string_tables.insert(language.to_string(), table);
+        }
+    }
+
+    async fn load_themes(&mut self) {
+        for id in constants::THEMES {
+            let json = load_string(&format!("themes/{}.json", id))
+                .await
+                .expect("Couldn't load theme file.");
+            let theme = Theme::from_json(&json)
+                .unwrap_or_else(|err| panic!("Couldn't parse theme {}: {}", id, err));
+            for file in theme.images() {
+                if !self.images.contains_key(file) {
+                    let image = load_image(file)
+                        .await
+                        .expect("Couldn't load image file.");
+                    self.images.insert(file.to_string(), image);
+                }
+            }
+            self.themes.insert(id.to_string(), theme);
+        }
+    }
+
+    async fn load_achievements(&mut self) {
+        let json = load_string("achievements.json")
             .await
-            .expect("Couldn't load file.");
-        self.fonts.insert(constants::FONT.to_string(), font);
+            .expect("Couldn't load achievements file.");
+        self.achievements = AchievementDefinition::list_from_json(&json)
+            .unwrap_or_else(|err| panic!("Couldn't parse achievements: {}", err));
     }
 
-}
+    async fn load_waves(&mut self) {
+        let json = load_string("waves.json")
+            .await
+            .expect("Couldn't load waves file.");
+        self.waves = WaveSet::from_json(&json)
+            .unwrap_or_else(|err| panic!("Couldn't parse waves: {}", err));
+    }
 
+}
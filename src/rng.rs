@@ -0,0 +1,44 @@
+/// Small seeded random number generator (xorshift64*).
+///
+/// Unlike `macroquad::rand`, which is global, every owner gets its own
+/// sequence, so a simulation started from the same seed plays out the same
+/// way on every machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Spread the seed with a splitmix64 step, xorshift gets stuck on 0
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Rng {
+            state: (z ^ (z >> 31)).max(1),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in `low..high`.
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        low + (high - low) * unit
+    }
+
+    /// Uniform in `0..count`, `count` must not be 0.
+    pub fn gen_index(&mut self, count: usize) -> usize {
+        (self.next_u64() % count as u64) as usize
+    }
+
+    /// `true` with the given probability.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.gen_range(0.0, 1.0) < probability
+    }
+}
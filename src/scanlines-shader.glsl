@@ -0,0 +1,15 @@
+#version 100
+
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 iResolution;
+
+void main() {
+    vec3 col = texture2D(Texture, uv).rgb;
+    float line = sin(uv.y * iResolution.y * 3.1415) * 0.5 + 0.5;
+    col *= mix(0.75, 1.0, line);
+    gl_FragColor = vec4(col, 1.0);
+}
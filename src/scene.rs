@@ -0,0 +1,193 @@
+use macroquad::prelude::*;
+use super::achievements::Achievements;
+use super::audio_backend::AudioBackend;
+use super::camera_effects::CameraEffects;
+use super::daily::DailyHistory;
+use super::high_scores::HighScores;
+use super::localization::Localization;
+use super::navigation::Navigation;
+use super::post_processing::PostProcessor;
+use super::resource_manager::ResourceManager;
+use super::settings::Settings;
+use super::sound_manager::SoundManager;
+use super::stats::LifetimeStats;
+use super::toasts::Toasts;
+use super::typography::Typography;
+use super::viewport::Viewport;
+use super::window_manager::WindowManager;
+
+/// State shared by all scenes.
+pub struct GameContext<'a> {
+    pub resource_manager: &'a ResourceManager,
+    pub sound_manager: SoundManager<Box<dyn AudioBackend + 'a>>,
+    pub window_manager: WindowManager<'a>,
+    pub localization: Localization<'a>,
+    pub typography: Typography<'a>,
+    pub navigation: Navigation,
+    pub viewport: Viewport,
+    pub post_processor: PostProcessor,
+    pub camera_effects: CameraEffects,
+    pub achievements: Achievements<'a>,
+    pub toasts: Toasts,
+    pub lifetime_stats: LifetimeStats,
+    pub settings: Settings,
+    pub high_scores: HighScores,
+    pub daily_history: DailyHistory,
+    /// Horizontal drift of the starfield background
+    pub direction_modifier: f32,
+}
+
+#[derive(Clone, Copy)]
+pub enum Transition {
+    Cut,
+    /// Fades to black and back, the scene change happens in the middle
+    Fade(f32),
+}
+
+pub enum SceneCommand {
+    None,
+    Push(Box<dyn Scene>, Transition),
+    Pop(Transition),
+    /// Drops the whole stack and starts over with the given scene
+    Reset(Box<dyn Scene>, Transition),
+    Quit,
+}
+
+pub trait Scene {
+    /// Called once when the scene is added to the stack.
+    fn on_enter(&mut self, _ctx: &mut GameContext) {}
+
+    /// Called once when the scene is removed from the stack.
+    fn on_exit(&mut self, _ctx: &mut GameContext) {}
+
+    /// Called when another scene is pushed on top of this one.
+    fn on_pause(&mut self, _ctx: &mut GameContext) {}
+
+    /// Called when this scene becomes the top of the stack again.
+    fn on_resume(&mut self, _ctx: &mut GameContext) {}
+
+    /// Only the top scene is updated.
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand;
+
+    fn draw(&mut self, ctx: &mut GameContext);
+
+    /// Overlays are drawn on top of the scene below them.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+struct ActiveTransition {
+    duration: f32,
+    elapsed: f32,
+    pending: Option<SceneCommand>,
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    transition: Option<ActiveTransition>,
+}
+
+impl SceneStack {
+    pub fn new(ctx: &mut GameContext, mut initial: Box<dyn Scene>) -> Self {
+        initial.on_enter(ctx);
+        SceneStack {
+            scenes: vec![initial],
+            transition: None,
+        }
+    }
+
+    /// Updates the top scene and applies its command.
+    /// Returns `false` once the game should quit.
+    pub fn update(&mut self, ctx: &mut GameContext) -> bool {
+        if let Some(transition) = &mut self.transition {
+            transition.elapsed += get_frame_time();
+            if transition.elapsed >= transition.duration / 2.0
+                && let Some(command) = transition.pending.take() {
+                return self.apply(ctx, command);
+            }
+            if transition.elapsed >= transition.duration {
+                self.transition = None;
+            }
+            return true;
+        }
+
+        let command = match self.scenes.last_mut() {
+            Some(scene) => scene.update(ctx),
+            None => return false,
+        };
+        let transition = match &command {
+            SceneCommand::Push(_, transition)
+            | SceneCommand::Pop(transition)
+            | SceneCommand::Reset(_, transition) => *transition,
+            SceneCommand::None | SceneCommand::Quit => Transition::Cut,
+        };
+        match transition {
+            Transition::Cut => self.apply(ctx, command),
+            Transition::Fade(duration) => {
+                self.transition = Some(ActiveTransition {
+                    duration,
+                    elapsed: 0.0,
+                    pending: Some(command),
+                });
+                true
+            }
+        }
+    }
+
+    /// Draws the visible scenes through the playfield camera.
+    pub fn draw(&mut self, ctx: &mut GameContext) {
+        set_camera(&ctx.camera_effects.apply(ctx.viewport.camera()));
+        let first_visible = self.scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in self.scenes[first_visible..].iter_mut() {
+            scene.draw(ctx);
+        }
+        set_camera(&ctx.post_processor.screen_camera());
+
+        if let Some(transition) = &self.transition {
+            let half = transition.duration / 2.0;
+            let alpha = 1.0 - (transition.elapsed - half).abs() / half;
+            draw_rectangle(
+                0.0,
+                0.0,
+                screen_width(),
+                screen_height(),
+                Color::new(0.0, 0.0, 0.0, alpha.clamp(0.0, 1.0))
+            );
+        }
+    }
+
+    fn apply(&mut self, ctx: &mut GameContext, command: SceneCommand) -> bool {
+        match command {
+            SceneCommand::None => {}
+            SceneCommand::Push(mut scene, _) => {
+                if let Some(top) = self.scenes.last_mut() {
+                    top.on_pause(ctx);
+                }
+                scene.on_enter(ctx);
+                self.scenes.push(scene);
+            }
+            SceneCommand::Pop(_) => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.on_exit(ctx);
+                }
+                match self.scenes.last_mut() {
+                    Some(top) => top.on_resume(ctx),
+                    None => return false,
+                }
+            }
+            SceneCommand::Reset(mut scene, _) => {
+                while let Some(mut old) = self.scenes.pop() {
+                    old.on_exit(ctx);
+                }
+                scene.on_enter(ctx);
+                self.scenes.push(scene);
+            }
+            SceneCommand::Quit => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,55 @@
+use macroquad::prelude::*;
+use crate::menu::Layout;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+
+const WINDOW_SIZE: Vec2 = vec2(560.0, 520.0);
+
+pub struct AchievementsScene;
+
+impl Scene for AchievementsScene {
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        let mut back = false;
+        let localization = &ctx.localization;
+        let achievements = &ctx.achievements;
+
+        let window_pos = vec2(
+            screen_width() / 2.0 - WINDOW_SIZE.x / 2.0,
+            screen_height() / 2.0 - WINDOW_SIZE.y / 2.0
+        );
+        ctx.window_manager.menu(
+            "achievements_window",
+            window_pos,
+            WINDOW_SIZE,
+            Layout::vertical(Rect::new(20.0, 10.0, 520.0, 480.0), 4.0),
+            &ctx.navigation,
+            |menu| {
+                menu.ui().label(vec2(150.0, -34.0), localization.get("achievements.title"));
+                for achievement in achievements.definitions() {
+                    let status = if achievements.is_unlocked(&achievement.id) {
+                        localization.get("achievements.unlocked").to_string()
+                    } else if let Some((value, target)) = achievements.progress(achievement) {
+                        format!("{}/{}", value, target)
+                    } else {
+                        localization.get("achievements.locked").to_string()
+                    };
+                    menu.info(localization.get(&format!("achievement.{}", achievement.id)), &status);
+                    menu.info(localization.get(&format!("achievement.{}.description", achievement.id)), "");
+                }
+                if menu.button(localization.get("achievements.back")) || menu.back() {
+                    back = true;
+                }
+            }
+        );
+        ctx.window_manager.move_window(
+            ctx.window_manager.get_window_id("achievements_window").unwrap(),
+            window_pos
+        );
+
+        if back {
+            return SceneCommand::Pop(Transition::Cut);
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, _ctx: &mut GameContext) {}
+}
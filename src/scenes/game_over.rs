@@ -0,0 +1,162 @@
+use macroquad::prelude::*;
+use crate::dialog::{Dialog, DialogHandle};
+use crate::game_mode::GameMode;
+use crate::navigation::NavAction;
+use crate::resource_manager;
+use crate::typography;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::stats::{self, RunStats};
+use crate::viewport::Anchor;
+use super::{MainMenuScene, PlayingScene};
+
+const LINE_SPACING: f32 = 10.0;
+
+/// Results of a run with its grade, shown over the playfield.
+pub struct GameOverScene {
+    stats: RunStats,
+    mode: GameMode,
+    /// Seed of the run, played again when racing ghosts
+    seed: u64,
+    /// Counted for the high scores, practice runs of a daily challenge aren't
+    scored: bool,
+    /// Place the score took in the high score table of the mode
+    rank: Option<usize>,
+    overwrite_dialog: Option<DialogHandle<bool>>,
+}
+
+impl GameOverScene {
+    pub fn new(stats: RunStats, mode: GameMode, seed: u64, scored: bool) -> Self {
+        GameOverScene {
+            stats,
+            mode,
+            seed,
+            scored,
+            rank: None,
+            overwrite_dialog: None,
+        }
+    }
+
+    fn enter_score(&mut self, ctx: &mut GameContext) {
+        self.rank = ctx.high_scores.insert(self.mode, self.stats.score);
+        ctx.high_scores.save();
+    }
+}
+
+impl Scene for GameOverScene {
+    fn on_enter(&mut self, ctx: &mut GameContext) {
+        ctx.sound_manager.stop_playing(resource_manager::constants::THEME_MUSIC)
+            .unwrap_or_else(|err| warn!("{}", err));
+
+        if self.mode == GameMode::Daily {
+            // The run already went into the daily history
+            return;
+        }
+        if let Some(displaced) = ctx.high_scores.displaced(self.mode, self.stats.score) {
+            // A saved score would fall out of the full table, the player may want to keep it
+            self.overwrite_dialog = Some(ctx.window_manager.open_dialog(Dialog::confirm(
+                ctx.localization.get("dialog.overwrite.title"),
+                &ctx.localization.format("dialog.overwrite.message", &[("score", &displaced)]),
+                ctx.localization.get("dialog.yes"),
+                ctx.localization.get("dialog.no")
+            )));
+        } else {
+            self.enter_score(ctx);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        if let Some(dialog) = &self.overwrite_dialog
+            && let Some(overwrite) = ctx.window_manager.dialog_result(dialog)
+        {
+            self.overwrite_dialog = None;
+            if overwrite {
+                self.enter_score(ctx);
+            }
+        }
+
+        if ctx.navigation.pressed(NavAction::Confirm) {
+            let scene = if ctx.settings.ghost {
+                PlayingScene::with_seed(ctx, self.mode, self.seed)
+            } else {
+                PlayingScene::new(ctx, self.mode)
+            };
+            return SceneCommand::Reset(Box::new(scene), Transition::Cut);
+        }
+        if ctx.navigation.pressed(NavAction::Back) {
+            return SceneCommand::Reset(Box::new(MainMenuScene::new()), Transition::Fade(0.5));
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, ctx: &mut GameContext) {
+        let max_width = ctx.viewport.width() - 40.0;
+        let top = ctx.viewport.anchor(Anchor::TopCenter, vec2(0.0, 20.0));
+        let localization = &ctx.localization;
+        let time_up = self.mode.time_limit().is_some_and(|limit| self.stats.time_survived >= limit);
+        let mut area = ctx.typography.draw(
+            localization.get(if time_up { "game_over.time_up" } else { "game_over.title" }),
+            typography::ALERT,
+            top,
+            Anchor::TopCenter
+        );
+        let mut next_line = |text: &str, style: &str| {
+            area = ctx.typography.draw_wrapped(
+                text,
+                style,
+                vec2(top.x, area.bottom() + LINE_SPACING),
+                Anchor::TopCenter,
+                max_width
+            );
+        };
+        match self.rank {
+            Some(0) => next_line(localization.get("game_over.high_score"), typography::ALERT),
+            Some(rank) => next_line(
+                &localization.format("game_over.rank", &[
+                    ("rank", &(rank + 1)),
+                    ("mode", &localization.get(&format!("mode.{}", self.mode)))
+                ]),
+                typography::BODY
+            ),
+            None => {}
+        }
+        if self.mode == GameMode::Daily {
+            let key = if self.scored { "game_over.daily" } else { "game_over.practice" };
+            next_line(localization.get(key), typography::BODY);
+        }
+        next_line(&localization.plural("game_over.points", self.stats.score, &[]), typography::BODY);
+        if self.stats.player_scores.len() > 1 {
+            for (index, score) in self.stats.player_scores.iter().enumerate() {
+                next_line(
+                    &localization.format("game_over.player_score", &[("number", &(index + 1)), ("score", score)]),
+                    typography::HUD
+                );
+            }
+        }
+        next_line(&localization.format("game_over.grade", &[("grade", &self.stats.grade())]), typography::TITLE);
+        next_line(
+            &localization.format("game_over.time", &[("time", &stats::format_duration(self.stats.time_survived))]),
+            typography::HUD
+        );
+        next_line(
+            &localization.format("game_over.shots", &[
+                ("shots", &self.stats.shots),
+                ("accuracy", &(self.stats.accuracy() * 100.0).round())
+            ]),
+            typography::HUD
+        );
+        next_line(&localization.format("game_over.combo", &[("combo", &self.stats.max_combo.max(1))]), typography::HUD);
+        let kills: Vec<(&str, u32)> = stats::ENEMY_KINDS
+            .iter()
+            .map(|(kind, _)| (*kind, self.stats.kills(kind)))
+            .collect();
+        let kill_args: Vec<(&str, &dyn std::fmt::Display)> = kills
+            .iter()
+            .map(|(kind, count)| (*kind, count as &dyn std::fmt::Display))
+            .collect();
+        next_line(&localization.format("game_over.kills", &kill_args), typography::HUD);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,208 @@
+use macroquad::prelude::*;
+use crate::menu::Layout;
+use crate::net_world::NetMode;
+use crate::netcode::{self, Connection, LinkConditions, Message};
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use super::NetPlayingScene;
+
+const WINDOW_SIZE: Vec2 = vec2(460.0, 400.0);
+const HELLO_INTERVAL_SECONDS: f64 = 0.25;
+/// Joining gives up when the host doesn't answer for this long
+const JOIN_TIMEOUT_SECONDS: f64 = 10.0;
+/// Simulated one-way delays to pick from, in milliseconds
+const LATENCY_OPTIONS: &[u32] = &[0, 50, 100, 200];
+/// Simulated packet loss to pick from, in percent
+const LOSS_OPTIONS: &[u32] = &[0, 5, 10, 25];
+/// Share of the simulated latency added as random jitter
+const JITTER_SHARE: f64 = 0.25;
+
+enum LobbyState {
+    Idle,
+    /// Waiting for someone to join
+    Hosting { connection: Connection, seed: u64 },
+    /// Saying hello until the host answers
+    Joining { connection: Connection, started: f64, last_hello: f64 },
+    Failed(String),
+}
+
+/// Hosts an online match or joins one by address.
+///
+/// Everything runs over UDP, so two instances on one machine can play over
+/// loopback. Latency and packet loss can be simulated to try the netcode
+/// under bad conditions.
+pub struct LobbyScene {
+    mode_index: usize,
+    address: String,
+    latency_index: usize,
+    loss_index: usize,
+    state: LobbyState,
+}
+
+impl LobbyScene {
+    pub fn new() -> Self {
+        LobbyScene {
+            mode_index: 0,
+            address: format!("127.0.0.1:{}", netcode::DEFAULT_PORT),
+            latency_index: 0,
+            loss_index: 0,
+            state: LobbyState::Idle,
+        }
+    }
+
+    fn conditions(&self) -> LinkConditions {
+        let latency = LATENCY_OPTIONS[self.latency_index] as f64 / 1000.0;
+        LinkConditions {
+            latency,
+            jitter: latency * JITTER_SHARE,
+            loss: LOSS_OPTIONS[self.loss_index] as f32 / 100.0,
+        }
+    }
+
+    /// Port of the address field, the host listens on it.
+    fn port(&self) -> u16 {
+        self.address
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse().ok())
+            .unwrap_or(netcode::DEFAULT_PORT)
+    }
+
+    fn host(&mut self) {
+        self.state = match Connection::host(self.port(), self.conditions()) {
+            Ok(connection) => LobbyState::Hosting {
+                connection,
+                seed: (rand::rand() as u64) << 32 | rand::rand() as u64,
+            },
+            Err(err) => LobbyState::Failed(err.to_string()),
+        };
+    }
+
+    fn join(&mut self) {
+        let now = get_time();
+        self.state = match Connection::join(&self.address, self.conditions()) {
+            Ok(connection) => LobbyState::Joining {
+                connection,
+                started: now,
+                last_hello: f64::NEG_INFINITY,
+            },
+            Err(err) => LobbyState::Failed(err.to_string()),
+        };
+    }
+
+    /// Handles the handshake, returns the match once both sides agree on it.
+    fn poll(&mut self, ctx: &GameContext) -> Option<NetPlayingScene> {
+        let now = get_time();
+        let mode = NetMode::ALL[self.mode_index];
+        let started = match &mut self.state {
+            LobbyState::Hosting { connection, seed } => {
+                let joined = connection.receive(now).contains(&Message::Hello);
+                if joined {
+                    connection.send(&Message::Welcome { seed: *seed, mode }, now);
+                }
+                joined.then_some((0, *seed, mode))
+            }
+            LobbyState::Joining { connection, started, last_hello } => {
+                if now - *last_hello >= HELLO_INTERVAL_SECONDS {
+                    connection.send(&Message::Hello, now);
+                    *last_hello = now;
+                }
+                let welcome = connection.receive(now).into_iter().find_map(|message| match message {
+                    Message::Welcome { seed, mode } => Some((1, seed, mode)),
+                    _ => None,
+                });
+                if welcome.is_none() && now - *started > JOIN_TIMEOUT_SECONDS {
+                    self.state = LobbyState::Failed(ctx.localization.get("lobby.no_answer").to_string());
+                    return None;
+                }
+                welcome
+            }
+            LobbyState::Idle | LobbyState::Failed(_) => None,
+        };
+        let (local, seed, mode) = started?;
+        let (LobbyState::Hosting { connection, .. } | LobbyState::Joining { connection, .. }) =
+            std::mem::replace(&mut self.state, LobbyState::Idle)
+        else {
+            return None;
+        };
+        Some(NetPlayingScene::new(ctx, connection, local, seed, mode))
+    }
+
+    fn status(&self, ctx: &GameContext) -> String {
+        let localization = &ctx.localization;
+        match &self.state {
+            LobbyState::Idle => localization.get("lobby.status.idle").to_string(),
+            LobbyState::Hosting { .. } => localization.format("lobby.status.hosting", &[("port", &self.port())]),
+            LobbyState::Joining { .. } => localization.format("lobby.status.joining", &[("address", &self.address)]),
+            LobbyState::Failed(error) => localization.format("lobby.status.failed", &[("error", error)]),
+        }
+    }
+}
+
+impl Scene for LobbyScene {
+    fn on_enter(&mut self, _ctx: &mut GameContext) {
+        // Don't let keys pressed in earlier scenes end up in the address
+        clear_input_queue();
+    }
+
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        if let Some(scene) = self.poll(ctx) {
+            return SceneCommand::Reset(Box::new(scene), Transition::Cut);
+        }
+
+        let (mut host, mut join, mut back) = (false, false, false);
+        let status = self.status(ctx);
+        let localization = &ctx.localization;
+        let mode_names: Vec<String> = NetMode::ALL
+            .iter()
+            .map(|mode| localization.get(&format!("lobby.mode.{}", mode)).to_string())
+            .collect();
+        let mode_labels: Vec<&str> = mode_names.iter().map(String::as_str).collect();
+        let latency_names: Vec<String> = LATENCY_OPTIONS.iter().map(|latency| format!("{} ms", latency)).collect();
+        let latency_labels: Vec<&str> = latency_names.iter().map(String::as_str).collect();
+        let loss_names: Vec<String> = LOSS_OPTIONS.iter().map(|loss| format!("{}%", loss)).collect();
+        let loss_labels: Vec<&str> = loss_names.iter().map(String::as_str).collect();
+        let busy = matches!(self.state, LobbyState::Hosting { .. } | LobbyState::Joining { .. });
+
+        let window_pos = vec2(
+            screen_width() / 2.0 - WINDOW_SIZE.x / 2.0,
+            screen_height() / 2.0 - WINDOW_SIZE.y / 2.0
+        );
+        ctx.window_manager.menu(
+            "lobby_window",
+            window_pos,
+            WINDOW_SIZE,
+            Layout::vertical(Rect::new(20.0, 10.0, 420.0, 340.0), 6.0),
+            &ctx.navigation,
+            |menu| {
+                menu.ui().label(vec2(130.0, -34.0), localization.get("lobby.title"));
+                menu.dropdown(localization.get("lobby.mode"), &mode_labels, &mut self.mode_index);
+                // Host names and IPv6 addresses are fine too, joining resolves it
+                menu.text_field(localization.get("lobby.address"), &mut self.address, |character| !character.is_control());
+                menu.dropdown(localization.get("lobby.latency"), &latency_labels, &mut self.latency_index);
+                menu.dropdown(localization.get("lobby.loss"), &loss_labels, &mut self.loss_index);
+                host = menu.small_button(localization.get("lobby.host"));
+                join = menu.small_button(localization.get("lobby.join"));
+                let back_label = if busy { "lobby.cancel" } else { "lobby.back" };
+                back = menu.button(localization.get(back_label)) || menu.back();
+                menu.ui().label(vec2(20.0, 360.0), &status);
+            }
+        );
+        ctx.window_manager.move_window(
+            ctx.window_manager.get_window_id("lobby_window").unwrap(),
+            window_pos
+        );
+
+        if host {
+            self.host();
+        } else if join {
+            self.join();
+        } else if back {
+            if !busy {
+                return SceneCommand::Pop(Transition::Cut);
+            }
+            self.state = LobbyState::Idle;
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, _ctx: &mut GameContext) {}
+}
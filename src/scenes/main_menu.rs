@@ -0,0 +1,109 @@
+use macroquad::prelude::*;
+use crate::dialog::{Dialog, DialogHandle};
+use crate::menu::Layout;
+use crate::resource_manager;
+use crate::typography;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::viewport::Anchor;
+#[cfg(not(target_arch = "wasm32"))]
+use super::LobbyScene;
+use super::{AchievementsScene, ModeSelectScene, SettingsScene, StatisticsScene};
+
+const WINDOW_SIZE: Vec2 = vec2(370.0, 495.0);
+/// Keeps the window clear of the title
+const WINDOW_OFFSET_Y: f32 = 40.0;
+
+pub struct MainMenuScene {
+    quit_dialog: Option<DialogHandle<bool>>,
+}
+
+impl MainMenuScene {
+    pub fn new() -> Self {
+        MainMenuScene {
+            quit_dialog: None,
+        }
+    }
+}
+
+impl Scene for MainMenuScene {
+    fn on_enter(&mut self, ctx: &mut GameContext) {
+        ctx.sound_manager.start_playing(resource_manager::constants::THEME_MUSIC, 0.3)
+            .unwrap_or_else(|err| warn!("{}", err));
+        ctx.sound_manager.set_volume(resource_manager::constants::THEME_MUSIC, 0.3)
+            .unwrap_or_else(|err| warn!("{}", err));
+    }
+
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        if let Some(dialog) = &self.quit_dialog
+            && let Some(quit) = ctx.window_manager.dialog_result(dialog)
+        {
+            self.quit_dialog = None;
+            if quit {
+                return SceneCommand::Quit;
+            }
+        }
+
+        let mut command = SceneCommand::None;
+        let mut quit = false;
+        let localization = &ctx.localization;
+
+        let window_pos = vec2(
+            screen_width() / 2.0 - WINDOW_SIZE.x / 2.0,
+            screen_height() / 2.0 - WINDOW_SIZE.y / 2.0 + WINDOW_OFFSET_Y
+        );
+        ctx.window_manager.menu(
+            "main_menu_window",
+            window_pos,
+            WINDOW_SIZE,
+            Layout::vertical(Rect::new(40.0, 25.0, 290.0, 390.0), 8.0),
+            &ctx.navigation,
+            |menu| {
+                menu.ui().label(vec2(80.0, -34.0), localization.get("menu.title"));
+                if menu.button(localization.get("menu.play")) {
+                    command = SceneCommand::Push(Box::new(ModeSelectScene::new()), Transition::Cut);
+                }
+                // Online play needs UDP, which the browser doesn't have
+                #[cfg(not(target_arch = "wasm32"))]
+                if menu.small_button(localization.get("menu.online")) {
+                    command = SceneCommand::Push(Box::new(LobbyScene::new()), Transition::Cut);
+                }
+                if menu.button(localization.get("menu.settings")) {
+                    command = SceneCommand::Push(Box::new(SettingsScene), Transition::Cut);
+                }
+                if menu.small_button(localization.get("menu.achievements")) {
+                    command = SceneCommand::Push(Box::new(AchievementsScene), Transition::Cut);
+                }
+                if menu.small_button(localization.get("menu.statistics")) {
+                    command = SceneCommand::Push(Box::new(StatisticsScene), Transition::Cut);
+                }
+                if menu.button(localization.get("menu.quit")) || menu.back() {
+                    quit = true;
+                }
+            }
+        );
+        ctx.window_manager.move_window(
+            ctx.window_manager.get_window_id("main_menu_window").unwrap(),
+            window_pos
+        );
+
+        if quit {
+            self.quit_dialog = Some(ctx.window_manager.open_dialog(Dialog::confirm(
+                ctx.localization.get("dialog.quit.title"),
+                ctx.localization.get("dialog.quit.message"),
+                ctx.localization.get("dialog.yes"),
+                ctx.localization.get("dialog.no")
+            )));
+        }
+        command
+    }
+
+    fn draw(&mut self, ctx: &mut GameContext) {
+        let title_position = ctx.viewport.anchor(Anchor::TopCenter, vec2(0.0, 30.0));
+        ctx.typography.draw(
+            ctx.localization.get("title"),
+            typography::TITLE,
+            title_position,
+            Anchor::TopCenter
+        );
+    }
+}
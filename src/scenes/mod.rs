@@ -0,0 +1,25 @@
+mod main_menu;
+mod playing;
+mod pause;
+mod game_over;
+mod settings;
+mod achievements;
+mod statistics;
+#[cfg(not(target_arch = "wasm32"))]
+mod lobby;
+#[cfg(not(target_arch = "wasm32"))]
+mod net_playing;
+mod mode_select;
+
+pub use main_menu::MainMenuScene;
+pub use playing::PlayingScene;
+pub use pause::PauseScene;
+pub use game_over::GameOverScene;
+pub use settings::SettingsScene;
+pub use achievements::AchievementsScene;
+pub use statistics::StatisticsScene;
+#[cfg(not(target_arch = "wasm32"))]
+pub use lobby::LobbyScene;
+#[cfg(not(target_arch = "wasm32"))]
+pub use net_playing::NetPlayingScene;
+pub use mode_select::ModeSelectScene;
@@ -0,0 +1,143 @@
+use macroquad::prelude::*;
+use crate::daily::DailyChallenge;
+use crate::game_mode::{Difficulty, GameMode};
+use crate::high_scores;
+use crate::menu::Layout;
+use crate::typography;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::viewport::Anchor;
+use super::PlayingScene;
+
+const WINDOW_SIZE: Vec2 = vec2(420.0, 454.0);
+/// Keeps the window clear of the rules
+const WINDOW_OFFSET_Y: f32 = 40.0;
+
+/// Picks the game mode of the next run, with the high score table of each mode.
+pub struct ModeSelectScene {
+    mode_index: usize,
+    daily: DailyChallenge,
+}
+
+impl ModeSelectScene {
+    pub fn new() -> Self {
+        ModeSelectScene {
+            mode_index: 0,
+            daily: DailyChallenge::today(),
+        }
+    }
+
+    /// Rules of the selected mode, with today's mutators for the daily challenge.
+    fn rules(&self, ctx: &GameContext) -> String {
+        let localization = &ctx.localization;
+        let mode = GameMode::ALL[self.mode_index];
+        if mode != GameMode::Daily {
+            return localization.get(&format!("mode.{}.rules", mode)).to_string();
+        }
+        let mutators: Vec<String> = self.daily.mutators
+            .iter()
+            .map(|mutator| localization.get(&format!("mutator.{}", mutator)).to_string())
+            .collect();
+        let mut rules = localization.format("mode.daily.rules", &[
+            ("date", &self.daily.date),
+            ("mutators", &mutators.join(", "))
+        ]);
+        if let Some(score) = ctx.daily_history.get_score(&self.daily.date) {
+            rules.push('\n');
+            rules.push_str(&localization.format("mode.daily.played", &[("score", &score)]));
+        }
+        rules
+    }
+}
+
+impl Scene for ModeSelectScene {
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        let (mut play, mut back) = (false, false);
+        let localization = &ctx.localization;
+        let mode_names: Vec<String> = GameMode::ALL
+            .iter()
+            .map(|mode| localization.get(&format!("mode.{}", mode)).to_string())
+            .collect();
+        let mode_labels: Vec<&str> = mode_names.iter().map(String::as_str).collect();
+        let difficulty_names: Vec<String> = Difficulty::ALL
+            .iter()
+            .map(|difficulty| localization.get(&format!("difficulty.{}", difficulty)).to_string())
+            .collect();
+        let difficulty_labels: Vec<&str> = difficulty_names.iter().map(String::as_str).collect();
+        let mut difficulty_index = Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == ctx.settings.difficulty)
+            .unwrap_or(0);
+        let mut adaptive = ctx.settings.adaptive_difficulty;
+        let rows: Vec<(String, String)> = if GameMode::ALL[self.mode_index] == GameMode::Daily {
+            // The latest days instead of a table, there's one score per day
+            let entries = ctx.daily_history.entries();
+            (0..high_scores::TABLE_SIZE)
+                .map(|day| entries.get(day).map_or(
+                    ("-".to_string(), "-".to_string()),
+                    |(date, score)| (date.clone(), score.to_string())
+                ))
+                .collect()
+        } else {
+            let table = ctx.high_scores.table(GameMode::ALL[self.mode_index]);
+            (0..high_scores::TABLE_SIZE)
+                .map(|rank| (
+                    format!("{}.", rank + 1),
+                    table.get(rank).map_or("-".to_string(), u32::to_string)
+                ))
+                .collect()
+        };
+
+        let window_pos = vec2(
+            screen_width() / 2.0 - WINDOW_SIZE.x / 2.0,
+            screen_height() / 2.0 - WINDOW_SIZE.y / 2.0 + WINDOW_OFFSET_Y
+        );
+        ctx.window_manager.menu(
+            "mode_select_window",
+            window_pos,
+            WINDOW_SIZE,
+            Layout::vertical(Rect::new(20.0, 10.0, 380.0, 414.0), 6.0),
+            &ctx.navigation,
+            |menu| {
+                menu.ui().label(vec2(130.0, -34.0), localization.get("mode_select.title"));
+                menu.dropdown(localization.get("mode_select.mode"), &mode_labels, &mut self.mode_index);
+                menu.dropdown(localization.get("mode_select.difficulty"), &difficulty_labels, &mut difficulty_index);
+                menu.toggle(localization.get("mode_select.adaptive"), &mut adaptive);
+                for (rank, score) in &rows {
+                    menu.info(rank, score);
+                }
+                play = menu.small_button(localization.get("mode_select.play"));
+                back = menu.small_button(localization.get("mode_select.back")) || menu.back();
+            }
+        );
+        ctx.window_manager.move_window(
+            ctx.window_manager.get_window_id("mode_select_window").unwrap(),
+            window_pos
+        );
+
+        let difficulty = Difficulty::ALL[difficulty_index];
+        if difficulty != ctx.settings.difficulty || adaptive != ctx.settings.adaptive_difficulty {
+            ctx.settings.difficulty = difficulty;
+            ctx.settings.adaptive_difficulty = adaptive;
+            ctx.settings.save();
+        }
+
+        if play {
+            let scene = PlayingScene::new(ctx, GameMode::ALL[self.mode_index]);
+            return SceneCommand::Reset(Box::new(scene), Transition::Fade(0.5));
+        }
+        if back {
+            return SceneCommand::Pop(Transition::Cut);
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, ctx: &mut GameContext) {
+        ctx.typography.draw_wrapped(
+            &self.rules(ctx),
+            typography::HUD,
+            ctx.viewport.anchor(Anchor::TopCenter, vec2(0.0, 30.0)),
+            Anchor::TopCenter,
+            ctx.viewport.width() - 40.0
+        );
+    }
+}
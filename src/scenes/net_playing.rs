@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use macroquad_particles::{Emitter, EmitterConfig};
+use crate::animation::AnimatedSprite;
+use crate::controls::{self, PlayerControls};
+use crate::dialog::{Dialog, DialogHandle};
+use crate::game_mode;
+use crate::navigation::NavAction;
+use crate::net_world::{self, NetEvent, NetInput, NetMode, NetWorld};
+use crate::netcode::{Connection, RollbackSession};
+use crate::resource_manager::{self, ResourceManager};
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::sound_manager::{SoundParams, Priority};
+use crate::typography;
+use crate::viewport::Anchor;
+use super::MainMenuScene;
+use super::playing::particle_explosion;
+
+/// Frames the world may catch up on in one render frame after a hitch
+const MAX_STEPS_PER_FRAME: u32 = 5;
+/// Invulnerable ships switch between shown and hidden every this many frames
+const BLINK_FRAMES: u32 = 4;
+
+/// An online match against or together with a peer, kept in sync by a `RollbackSession`.
+pub struct NetPlayingScene {
+    session: RollbackSession,
+    controls: PlayerControls,
+    /// Time not simulated yet, the world steps at a fixed rate
+    accumulator: f32,
+    /// Fire was pressed since the last step, so presses between steps aren't lost
+    fire_pressed: bool,
+    /// One sprite per texture, all objects of a kind share its animation
+    sprites: HashMap<&'static str, AnimatedSprite>,
+    explosions: Vec<(Emitter, Vec2)>,
+    leave_dialog: Option<DialogHandle<bool>>,
+    disconnected_dialog: Option<DialogHandle<bool>>,
+}
+
+impl NetPlayingScene {
+    pub fn new(ctx: &GameContext, connection: Connection, local: usize, seed: u64, mode: NetMode) -> Self {
+        let mut sprites = HashMap::new();
+        for texture_id in [resource_manager::constants::PLAYER_TEX_ID, resource_manager::constants::BULLET_TEX_ID]
+            .iter()
+            .chain(resource_manager::constants::ENEMY_TEXTURES)
+        {
+            sprites.insert(*texture_id, ctx.resource_manager.get_animated_sprite(texture_id).unwrap());
+        }
+        if let Some(bullet) = sprites.get_mut(resource_manager::constants::BULLET_TEX_ID) {
+            bullet.set_animation("bolt");
+        }
+        let enemy_sizes = resource_manager::constants::ENEMY_TEXTURES
+            .iter()
+            .map(|texture_id| sprites[texture_id].frame().dest_size)
+            .collect();
+        NetPlayingScene {
+            session: RollbackSession::new(connection, local, seed, NetWorld::new(seed, mode, enemy_sizes)),
+            controls: PlayerControls::new(&controls::SOLO_KEYS, 0),
+            accumulator: 0.0,
+            fire_pressed: false,
+            sprites,
+            explosions: vec![],
+            leave_dialog: None,
+            disconnected_dialog: None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.session.world().is_over() && self.session.is_confirmed()
+    }
+
+    fn play_events(&mut self, ctx: &mut GameContext, events: Vec<NetEvent>) {
+        for event in events {
+            match event {
+                NetEvent::Shot { position } => {
+                    ctx.sound_manager.play_once(
+                        resource_manager::constants::LASER_SOUND,
+                        SoundParams {
+                            volume: 0.6,
+                            pitch_variation: 0.08,
+                            priority: Priority::Low,
+                            ..Default::default()
+                        }.at_x(position.x, net_world::WORLD_SIZE.x)
+                    ).unwrap_or_else(|err| warn!("{}", err));
+                }
+                NetEvent::Kill { position, size } => {
+                    ctx.camera_effects.add_trauma(0.15);
+                    self.explode(ctx, position, size, SoundParams {
+                        volume: 0.8,
+                        pitch_variation: 0.12,
+                        ..Default::default()
+                    });
+                }
+                NetEvent::ShipHit { position } => {
+                    ctx.camera_effects.add_trauma(0.8);
+                    self.explode(ctx, position, game_mode::SHIP_SIZE.y, SoundParams {
+                        priority: Priority::Critical,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    fn explode(&mut self, ctx: &mut GameContext, position: Vec2, size: f32, sound: SoundParams) {
+        let explosion_texture = ctx.resource_manager
+            .get_texture(resource_manager::constants::EXPLOSION_TEX_ID).unwrap();
+        self.explosions.push((
+            Emitter::new(EmitterConfig {
+                amount: size.round() as u32,
+                texture: Some(explosion_texture.clone()),
+                ..particle_explosion()
+            }),
+            position
+        ));
+        ctx.sound_manager.play_once(
+            resource_manager::constants::EXPLOSION_SOUND,
+            sound.at_x(position.x, net_world::WORLD_SIZE.x)
+        ).unwrap_or_else(|err| warn!("{}", err));
+    }
+
+    fn draw_sprite(&self, resource_manager: &ResourceManager, texture_id: &str, center: Vec2, size: Vec2) {
+        let texture = resource_manager.get_texture(texture_id).unwrap();
+        draw_texture_ex(
+            texture,
+            center.x - size.x / 2.0,
+            center.y - size.y / 2.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(size),
+                source: Some(self.sprites[texture_id].frame().source_rect),
+                ..Default::default()
+            }
+        );
+    }
+
+    fn draw_ship_hud(&self, ctx: &GameContext, index: usize, anchor: Anchor) {
+        let ship = &self.session.world().ships[index];
+        let localization = &ctx.localization;
+        let mut position = ctx.viewport.anchor(anchor, vec2(10.0, 25.0));
+        let mut next_line = |text: &str| {
+            let area = ctx.typography.draw(text, typography::HUD, position, anchor);
+            position.y = area.bottom() + 8.0;
+        };
+        let name_key = if index == self.session.local_player() { "net.you" } else { "hud.player" };
+        next_line(&localization.format(name_key, &[("number", &(index + 1))]));
+        next_line(&localization.format("hud.score", &[("score", &ship.score)]));
+        if ship.is_out() {
+            next_line(localization.get("hud.out"));
+        } else {
+            next_line(&localization.format("hud.lives", &[("lives", &ship.lives)]));
+        }
+    }
+}
+
+impl Scene for NetPlayingScene {
+    fn on_enter(&mut self, ctx: &mut GameContext) {
+        ctx.sound_manager.start_playing(resource_manager::constants::THEME_MUSIC, 0.7)
+            .unwrap_or_else(|err| warn!("{}", err));
+        ctx.sound_manager.set_volume(resource_manager::constants::THEME_MUSIC, 0.7)
+            .unwrap_or_else(|err| warn!("{}", err));
+    }
+
+    fn on_exit(&mut self, _ctx: &mut GameContext) {
+        self.session.quit(get_time());
+    }
+
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        let leave = self.leave_dialog
+            .as_ref()
+            .and_then(|dialog| ctx.window_manager.dialog_result(dialog));
+        let acknowledged = self.disconnected_dialog
+            .as_ref()
+            .and_then(|dialog| ctx.window_manager.dialog_result(dialog));
+        if leave == Some(false) {
+            self.leave_dialog = None;
+        }
+        if leave == Some(true) || acknowledged.is_some() {
+            return SceneCommand::Reset(Box::new(MainMenuScene::new()), Transition::Fade(0.5));
+        }
+
+        if self.is_finished() {
+            if ctx.navigation.pressed(NavAction::Confirm) || ctx.navigation.pressed(NavAction::Back) {
+                return SceneCommand::Reset(Box::new(MainMenuScene::new()), Transition::Fade(0.5));
+            }
+        } else if self.session.is_disconnected() {
+            if self.disconnected_dialog.is_none() {
+                if let Some(dialog) = self.leave_dialog.take() {
+                    ctx.window_manager.close_dialog(&dialog);
+                }
+                self.disconnected_dialog = Some(ctx.window_manager.open_dialog(
+                    Dialog::new(
+                        ctx.localization.get("dialog.disconnected.title"),
+                        ctx.localization.get("dialog.disconnected.message")
+                    ).choice(ctx.localization.get("dialog.ok"), true)
+                ));
+            }
+        } else if ctx.navigation.pressed(NavAction::Back) && self.leave_dialog.is_none() {
+            // An online match can't be paused, it keeps running below the dialog
+            self.leave_dialog = Some(ctx.window_manager.open_dialog(Dialog::confirm(
+                ctx.localization.get("dialog.leave.title"),
+                ctx.localization.get("dialog.leave.message"),
+                ctx.localization.get("dialog.yes"),
+                ctx.localization.get("dialog.no")
+            )));
+        }
+
+        let delta_time = get_frame_time();
+        let input = self.controls.read(&ctx.navigation);
+        self.fire_pressed |= input.fire;
+        self.accumulator = (self.accumulator + delta_time).min(net_world::FRAME_TIME * MAX_STEPS_PER_FRAME as f32);
+        while self.accumulator >= net_world::FRAME_TIME {
+            self.accumulator -= net_world::FRAME_TIME;
+            let frame_input = NetInput::new(input.movement, self.fire_pressed);
+            if let Some(events) = self.session.advance(frame_input, get_time()) {
+                self.fire_pressed = false;
+                self.play_events(ctx, events);
+            }
+        }
+
+        for sprite in self.sprites.values_mut() {
+            sprite.update(delta_time);
+        }
+        self.explosions.retain(|(explosion, _)| explosion.config.emitting);
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, ctx: &mut GameContext) {
+        let world = self.session.world();
+        for ship in &world.ships {
+            if !ship.is_out() && (ship.invulnerable / BLINK_FRAMES).is_multiple_of(2) {
+                self.draw_sprite(ctx.resource_manager, resource_manager::constants::PLAYER_TEX_ID, ship.position, game_mode::SHIP_SIZE);
+            }
+        }
+        for bullet in &world.bullets {
+            self.draw_sprite(ctx.resource_manager, resource_manager::constants::BULLET_TEX_ID, bullet.position, game_mode::BULLET_SIZE);
+        }
+        for enemy in &world.enemies {
+            let texture_id = resource_manager::constants::ENEMY_TEXTURES[enemy.kind];
+            self.draw_sprite(ctx.resource_manager, texture_id, enemy.position, enemy.size);
+        }
+        for (explosion, coords) in self.explosions.iter_mut() {
+            explosion.draw(*coords);
+        }
+
+        let world = self.session.world();
+        self.draw_ship_hud(ctx, 0, Anchor::TopLeft);
+        self.draw_ship_hud(ctx, 1, Anchor::TopRight);
+        if world.mode == NetMode::Coop {
+            let team_score: u32 = world.ships.iter().map(|ship| ship.score).sum();
+            ctx.typography.draw(
+                &ctx.localization.format("hud.score", &[("score", &team_score)]),
+                typography::HUD,
+                ctx.viewport.anchor(Anchor::TopCenter, vec2(0.0, 25.0)),
+                Anchor::TopCenter
+            );
+        }
+
+        ctx.typography.draw(
+            &ctx.localization.format("net.rollbacks", &[("count", &self.session.rollbacks())]),
+            typography::DEBUG,
+            ctx.viewport.anchor(Anchor::TopCenter, vec2(0.0, 60.0)),
+            Anchor::TopCenter
+        );
+
+        let center = ctx.viewport.anchor(Anchor::Center, Vec2::ZERO);
+        if self.is_finished() {
+            let result = match (world.mode, world.winner()) {
+                (NetMode::Coop, _) => ctx.localization.get("game_over.title").to_string(),
+                (NetMode::Versus, Some(winner)) => ctx.localization.format("net.winner", &[("number", &(winner + 1))]),
+                (NetMode::Versus, None) => ctx.localization.get("net.draw").to_string(),
+            };
+            let area = ctx.typography.draw(&result, typography::ALERT, center, Anchor::Center);
+            ctx.typography.draw(
+                ctx.localization.get("net.continue"),
+                typography::HUD,
+                vec2(center.x, area.bottom() + 20.0),
+                Anchor::TopCenter
+            );
+        } else if self.session.is_waiting() {
+            ctx.typography.draw(ctx.localization.get("net.waiting"), typography::HUD, center, Anchor::Center);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use macroquad::prelude::*;
+use crate::dialog::{Dialog, DialogHandle};
+use crate::navigation::NavAction;
+use crate::resource_manager;
+use crate::typography;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::viewport::Anchor;
+use super::MainMenuScene;
+
+pub struct PauseScene {
+    abandon_dialog: Option<DialogHandle<bool>>,
+}
+
+impl PauseScene {
+    pub fn new() -> Self {
+        PauseScene {
+            abandon_dialog: None,
+        }
+    }
+}
+
+impl Scene for PauseScene {
+    fn on_enter(&mut self, ctx: &mut GameContext) {
+        ctx.sound_manager.stop_playing(resource_manager::constants::THEME_MUSIC)
+            .unwrap_or_else(|err| warn!("{}", err));
+    }
+
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        if let Some(dialog) = &self.abandon_dialog
+            && let Some(abandon) = ctx.window_manager.dialog_result(dialog)
+        {
+            self.abandon_dialog = None;
+            if abandon {
+                return SceneCommand::Reset(Box::new(MainMenuScene::new()), Transition::Fade(0.5));
+            }
+        }
+
+        if ctx.navigation.pressed(NavAction::Confirm) {
+            return SceneCommand::Pop(Transition::Cut);
+        }
+        if ctx.navigation.pressed(NavAction::Back) {
+            self.abandon_dialog = Some(ctx.window_manager.open_dialog(Dialog::confirm(
+                ctx.localization.get("dialog.abandon.title"),
+                ctx.localization.get("dialog.abandon.message"),
+                ctx.localization.get("dialog.yes"),
+                ctx.localization.get("dialog.no")
+            )));
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, ctx: &mut GameContext) {
+        ctx.typography.draw(
+            ctx.localization.get("pause.title"),
+            typography::HEADING,
+            ctx.viewport.anchor(Anchor::Center, Vec2::ZERO),
+            Anchor::Center
+        );
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,1384 @@
+use macroquad::prelude::*;
+use macroquad_particles::{self as particles, AtlasConfig, ColorCurve, Emitter, EmitterConfig};
+use crate::achievements::GameEvent;
+use crate::asteroid::Asteroid;
+use crate::audio_backend::AudioBackend;
+use crate::animator::{AnimationEvent, Animator, Condition, State, StateTransition};
+use crate::combo::Combo;
+use crate::controls::{self, PlayerControls};
+use crate::daily::{self, DailyChallenge};
+use crate::director::Director;
+use crate::game_mode::{self, GameMode, Tuning, RESPAWN_INVULNERABILITY_SECONDS};
+use crate::game_object::GameObject;
+use crate::ghost::{GhostFrame, GhostRun};
+use crate::navigation::NavAction;
+use crate::resource_manager::{self, ResourceManager};
+use crate::rng::Rng;
+use crate::movement::Movement;
+use crate::behaviour::{Action, Behaviour, Brain, State as BrainState};
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::score_popup::ScorePopups;
+use crate::settings::LivesRule;
+use crate::typography;
+use crate::stats::{self, RunStats};
+use crate::sound_manager::{SoundManager, SoundParams, Priority};
+use crate::viewport::Anchor;
+use super::{GameOverScene, PauseScene};
+
+const SIDE_ANIMATION_SWITCH_SECONDS: f32 = 0.5;
+const BIG_KILL_HEIGHT: f32 = 100.0;
+const COMBO_BAR_SIZE: Vec2 = vec2(120.0, 4.0);
+const MAX_PLAYERS: usize = 2;
+/// Invulnerable players blink this many times per second
+const BLINK_RATE: f32 = 8.0;
+/// Sideways input the ship banks at, sticks are analog
+const STEER_THRESHOLD: f32 = 0.5;
+/// The run is simulated in fixed steps, so a seed plays out the same way at any frame rate
+const TICK_RATE: usize = 60;
+const STEP_TIME: f32 = 1.0 / TICK_RATE as f32;
+/// Steps the run may catch up on in one render frame after a hitch
+const MAX_STEPS_PER_FRAME: u32 = 5;
+const GHOST_TINT: Color = Color::new(0.6, 0.8, 1.0, 0.35);
+/// Ships that survive a hit pass through enemies for this long
+const DAMAGE_INVULNERABILITY_SECONDS: f32 = 1.0;
+/// Enemies that survive a hit flash for this long
+const HIT_FLASH_SECONDS: f32 = 0.1;
+const HIT_FLASH_TINT: Color = Color::new(1.0, 0.4, 0.4, 1.0);
+const HEALTH_BAR_SIZE: Vec2 = vec2(48.0, 4.0);
+const ENEMY_BULLET_SPEED: f32 = 350.0;
+const ENEMY_BULLET_DAMAGE: u32 = 1;
+const ENEMY_BULLET_TINT: Color = Color::new(1.0, 0.5, 0.3, 1.0);
+/// Blinks on enemies lining up a dive
+const AIM_TINT: Color = Color::new(1.0, 0.8, 0.2, 1.0);
+const SHIELD_COLOR: Color = Color::new(0.4, 0.8, 1.0, 0.8);
+/// Size of enemies released by spawners, fixed so shooting a spawner early
+/// doesn't change what spawns after it
+const RELEASED_SIZE: f32 = 2.5;
+/// Share of a knockback left after a second
+const KNOCKBACK_DAMPING: f32 = 0.02;
+
+pub fn particle_explosion() -> particles::EmitterConfig {
+    particles::EmitterConfig {
+        local_coords: false,
+        one_shot: true,
+        emitting: true,
+        lifetime: 1.2,
+        lifetime_randomness: 0.3,
+        explosiveness: 0.65,
+        initial_direction_spread: 2.0 * std::f32::consts::PI,
+        initial_velocity: 200.0,
+        initial_velocity_randomness: 0.8,
+        size: 16.0,
+        size_randomness: 0.3,
+        atlas: Some(AtlasConfig::new(5, 1, 0..)),
+        ..Default::default()
+    }
+}
+
+fn particle_engine() -> particles::EmitterConfig {
+    particles::EmitterConfig {
+        local_coords: false,
+        one_shot: false,
+        emitting: false,
+        lifetime: 0.6,
+        lifetime_randomness: 0.3,
+        explosiveness: 0.65,
+        initial_direction: vec2(0.0, 1.0),
+        initial_direction_spread: 0.3 * std::f32::consts::PI,
+        initial_velocity: 300.0,
+        initial_velocity_randomness: 0.8,
+        size: 3.0,
+        size_randomness: 0.3,
+        colors_curve: ColorCurve {
+            start: SKYBLUE,
+            mid: SKYBLUE,
+            end: SKYBLUE
+        },
+        ..Default::default()
+    }
+}
+
+/// Banks the ship into turns, fully after holding a direction for a while.
+/// The `horizontal` parameter is -1 while steering left and 1 while steering right.
+fn player_animator() -> Animator {
+    let mut transitions = vec![];
+    for (side, other_side, sign) in [("left", "right", -1.0), ("right", "left", 1.0)] {
+        let slight = format!("slight-{}", side);
+        let other_slight = format!("slight-{}", other_side);
+        let steering = if sign < 0.0 {
+            Condition::Less("horizontal".to_string(), 0.0)
+        } else {
+            Condition::Greater("horizontal".to_string(), 0.0)
+        };
+        transitions.push(StateTransition::new(&[&slight], side)
+            .when(steering.clone())
+            .when(Condition::TimeInState(SIDE_ANIMATION_SWITCH_SECONDS)));
+        transitions.push(StateTransition::new(&[&slight, side], "idle")
+            .when(Condition::Equals("horizontal".to_string(), 0.0)));
+        transitions.push(StateTransition::new(&["idle", &other_slight, other_side], &slight)
+            .when(Condition::Equals("horizontal".to_string(), sign)));
+    }
+    Animator::new(
+        vec![
+            State::looping("idle", "idle"),
+            State::looping("slight-left", "slight-left"),
+            State::looping("left", "left"),
+            State::looping("slight-right", "slight-right"),
+            State::looping("right", "right"),
+        ],
+        transitions
+    )
+}
+
+/// Bullets leave as a small spark and turn into a bolt once it has played.
+fn bullet_animator() -> Animator {
+    Animator::new(
+        vec![
+            State::once("launch", "bullet", "flight"),
+            State::looping("flight", "bolt"),
+        ],
+        vec![]
+    )
+}
+
+/// Strafers play their `animation` once more when they fire, the bolt leaves
+/// on the "fire" event of its second frame. Setting the `fire` parameter starts it.
+fn strafer_animator(animation: &str) -> Animator {
+    Animator::new(
+        vec![
+            State::looping("fly", animation),
+            State::once("fire", animation, "fly").on_frame(1, "fire"),
+        ],
+        vec![
+            StateTransition::new(&["fly"], "fire")
+                .when(Condition::Greater("fire".to_string(), 0.0)),
+        ]
+    )
+}
+
+struct Player {
+    object: GameObject,
+    engine: Emitter,
+    controls: PlayerControls,
+    /// Movement read in the last render frame, used by the steps until the next one
+    movement: Vec2,
+    /// Fire was pressed since the last step, so presses between steps aren't lost
+    fire_pressed: bool,
+    /// Own lives, only counted with `LivesRule::Separate`
+    lives: u32,
+    /// Health left of the current life
+    health: u32,
+    /// Velocity an asteroid knocked the ship into, fades out
+    knockback: Vec2,
+    combo: Combo,
+    /// Seconds left of the invulnerability after a respawn
+    invulnerable: f32,
+    /// Run time of the last shot
+    last_shot_time: f32,
+    /// Ran out of lives, the ship is gone for the rest of the run
+    out: bool,
+}
+
+impl Player {
+    fn new(ctx: &GameContext, controls: PlayerControls, position: Vec2, tuning: &Tuning) -> Self {
+        let object = ship_object(ctx.resource_manager, position);
+        let engine = Emitter::new(EmitterConfig {
+            amount: object.height.round() as u32 * 2,
+            emitting: true,
+            ..particle_engine()
+        });
+        Player {
+            object,
+            engine,
+            controls,
+            movement: Vec2::ZERO,
+            fire_pressed: false,
+            lives: tuning.lives,
+            health: tuning.ship_health,
+            knockback: Vec2::ZERO,
+            combo: Combo::new(),
+            invulnerable: 0.0,
+            last_shot_time: f32::NEG_INFINITY,
+            out: false,
+        }
+    }
+
+    /// Hidden every other blink while invulnerable.
+    fn is_visible(&self) -> bool {
+        !self.out && ((self.invulnerable * BLINK_RATE) as u32).is_multiple_of(2)
+    }
+}
+
+struct Enemy {
+    object: GameObject,
+    /// Index into the archetypes of the wave set
+    archetype: usize,
+    /// Hits left until it goes down
+    health: u32,
+    /// Seconds left of the flash after a hit it survived
+    flash: f32,
+    movement: Movement,
+    /// Steers it instead of the movement when set
+    brain: Option<Brain>,
+    /// Where it spawned, the movement is relative to it
+    origin: Vec2,
+    /// Seconds since it spawned
+    age: f32,
+}
+
+impl Enemy {
+    /// Moves it for one step, `targets` are the ships still flying.
+    fn fly(&mut self, delta_time: f32, targets: &[Vec2], playfield: Vec2) -> Option<Action> {
+        self.age += delta_time;
+        if let Some(brain) = &mut self.brain {
+            let position = vec2(self.object.x, self.object.y);
+            let target = targets
+                .iter()
+                .min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)))
+                .copied();
+            let action = brain.update(&mut self.object, target, delta_time, playfield);
+            // The shot waits for the frame of the firing animation
+            if action == Some(Action::Fire)
+                && let Some(animator) = &mut self.object.animator
+            {
+                animator.set_param("fire", 1.0);
+                return None;
+            }
+            return action;
+        }
+        let position = self.origin + self.movement.offset(self.age, self.object.speed, playfield);
+        self.object.x = position.x;
+        self.object.y = position.y;
+        None
+    }
+
+    /// Advances its sprite, returns the shot once a firing animation gets to it.
+    fn animate(&mut self, delta_time: f32) -> Option<Action> {
+        let mut action = None;
+        for event in self.object.animate(delta_time) {
+            match event {
+                AnimationEvent::Entered(state) if state == "fire" => {
+                    if let Some(animator) = &mut self.object.animator {
+                        animator.set_param("fire", 0.0);
+                    }
+                }
+                AnimationEvent::Frame(_, event) if event == "fire" => action = Some(Action::Fire),
+                _ => {}
+            }
+        }
+        action
+    }
+
+    fn tint(&self) -> Color {
+        if self.flash > 0.0 {
+            return HIT_FLASH_TINT;
+        }
+        match &self.brain {
+            Some(brain) if brain.state() == BrainState::Aim && ((self.age * BLINK_RATE) as u32).is_multiple_of(2) => AIM_TINT,
+            _ => WHITE,
+        }
+    }
+}
+
+/// The best earlier run with the same seed, replayed from its inputs.
+struct Ghost {
+    object: GameObject,
+    run: GhostRun,
+    /// Seconds left of the invulnerability after a respawn
+    invulnerable: f32,
+}
+
+impl Ghost {
+    fn new(ctx: &GameContext, run: GhostRun) -> Self {
+        Ghost {
+            object: ship_object(ctx.resource_manager, spawn_position(ctx, 0, 1)),
+            run,
+            invulnerable: 0.0,
+        }
+    }
+
+    /// Plays back the recorded step, the ghost is gone once its run ended.
+    fn step(&mut self, ctx: &GameContext, step: usize) {
+        let Some(frame) = self.run.get_frame(step).copied() else {
+            return;
+        };
+        self.invulnerable = (self.invulnerable - STEP_TIME).max(0.0);
+        steer(&mut self.object, frame.movement(), frame.knockback, STEP_TIME, vec2(ctx.viewport.width(), ctx.viewport.height()));
+        self.object.animate(STEP_TIME);
+        if frame.hit {
+            let respawn_position = spawn_position(ctx, 0, 1);
+            self.object.x = respawn_position.x;
+            self.object.y = respawn_position.y;
+            self.invulnerable = RESPAWN_INVULNERABILITY_SECONDS;
+        }
+    }
+
+    fn is_visible(&self, steps: usize) -> bool {
+        steps < self.run.frames.len() && ((self.invulnerable * BLINK_RATE) as u32).is_multiple_of(2)
+    }
+}
+
+fn explosion(ctx: &GameContext, object: &GameObject) -> (Emitter, Vec2) {
+    let explosion_texture = ctx.resource_manager
+        .get_texture(resource_manager::constants::EXPLOSION_TEX_ID).unwrap();
+    (
+        Emitter::new(EmitterConfig {
+            amount: object.height.round() as u32,
+            texture: Some(explosion_texture.clone()),
+            ..particle_explosion()
+        }),
+        vec2(object.x, object.y)
+    )
+}
+
+/// Explosion sound of a destroyed enemy, panned to where it was.
+fn play_enemy_explosion<B: AudioBackend>(sound_manager: &mut SoundManager<B>, x: f32, playfield_width: f32) {
+    sound_manager.play_once(
+        resource_manager::constants::EXPLOSION_SOUND,
+        SoundParams {
+            volume: 0.8,
+            pitch_variation: 0.12,
+            ..Default::default()
+        }.at_x(x, playfield_width)
+    ).unwrap_or_else(|err| warn!("{}", err));
+}
+
+/// Takes `damage` off the health of a ship and breaks its combo, even when
+/// the hit doesn't cost a life. Returns whether the ship is destroyed.
+fn damage_ship(health: &mut u32, combo: &mut Combo, damage: u32) -> bool {
+    combo.break_chain();
+    *health = health.saturating_sub(damage);
+    *health == 0
+}
+
+fn ship_object(resource_manager: &ResourceManager, position: Vec2) -> GameObject {
+    GameObject {
+        width: game_mode::SHIP_SIZE.x,
+        height: game_mode::SHIP_SIZE.y,
+        speed: game_mode::SHIP_SPEED,
+        x: position.x,
+        y: position.y,
+        rotation: 0.0,
+        collided: false,
+        texture_id: resource_manager::constants::PLAYER_TEX_ID.to_string(),
+        sprite: resource_manager.get_animated_sprite(resource_manager::constants::PLAYER_TEX_ID).unwrap(),
+        animator: Some(player_animator()),
+    }
+}
+
+/// Moves the ship and banks it into the turn, it stays within the playfield.
+/// `push` is a velocity it was knocked into on top of its own movement.
+fn steer(object: &mut GameObject, movement: Vec2, push: Vec2, delta_time: f32, playfield: Vec2) {
+    object.x += (object.speed * movement.x + push.x) * delta_time;
+    object.y += (object.speed * movement.y + push.y) * delta_time;
+    let horizontal = if movement.x.abs() >= STEER_THRESHOLD {
+        movement.x.signum()
+    } else {
+        0.0
+    };
+    if let Some(animator) = &mut object.animator {
+        animator.set_param("horizontal", horizontal);
+    }
+
+    // Clamp X and Y of player circle to be within the playfield
+    object.x = clamp(object.x, 0.0 + object.width, playfield.x - object.width);
+    object.y = clamp(object.y, 0.0 + object.height, playfield.y - object.height);
+}
+
+/// Where the player with `index` starts when `count` players are playing,
+/// they line up across the middle of the playfield.
+fn spawn_position(ctx: &GameContext, index: usize, count: usize) -> Vec2 {
+    vec2(
+        ctx.viewport.width() * (index + 1) as f32 / (count + 1) as f32,
+        ctx.viewport.height() / 2.0
+    )
+}
+
+/// A run, alone or with a second player who drops in on the same keyboard or a gamepad,
+/// played by the rules of its `GameMode`.
+///
+/// Enemies come from a seeded `Rng`, so runs with the same seed face the same
+/// waves. Solo runs are recorded and the best one of each seed races along as
+/// a ghost when the seed is played again.
+pub struct PlayingScene {
+    mode: GameMode,
+    tuning: Tuning,
+    seed: u64,
+    /// Date of the daily challenge while the run is its scored attempt
+    daily_date: Option<String>,
+    /// Adjusts `tuning` to how the run goes when adaptive difficulty is on
+    director: Option<Director>,
+    rng: Rng,
+    /// Time not simulated yet
+    accumulator: f32,
+    /// Steps simulated so far
+    steps: usize,
+    players: Vec<Player>,
+    /// Bullets with the index of the player who fired them
+    bullets: Vec<(GameObject, usize)>,
+    enemy_bullets: Vec<GameObject>,
+    asteroids: Vec<Asteroid>,
+    enemies: Vec<Enemy>,
+    explosions: Vec<(Emitter, Vec2)>,
+    stats: RunStats,
+    score_popups: ScorePopups,
+    lives_rule: LivesRule,
+    /// Lives all players draw from with `LivesRule::Shared`
+    team_lives: u32,
+    /// Set once all players are out, the game ends when the hit-stop is over
+    game_over: bool,
+    /// Inputs of a solo run, dropped when a second player joins
+    recording: Option<GhostRun>,
+    ghost: Option<Ghost>,
+}
+
+impl PlayingScene {
+    pub fn new(ctx: &GameContext, mode: GameMode) -> Self {
+        let seed = match mode {
+            GameMode::Daily => DailyChallenge::today().seed,
+            _ => (rand::rand() as u64) << 32 | rand::rand() as u64,
+        };
+        Self::with_seed(ctx, mode, seed)
+    }
+
+    /// Starts a run on the given seed, racing its best run when ghosts are on.
+    ///
+    /// A daily run on today's seed is scored if it's the first of the day,
+    /// all other runs of the challenge are practice.
+    pub fn with_seed(ctx: &GameContext, mode: GameMode, seed: u64) -> Self {
+        let mut tuning = mode.tuning();
+        let mut daily_date = None;
+        let mut director = None;
+        if mode == GameMode::Daily {
+            // Everyone gets the same challenge, no matter their difficulty
+            for mutator in daily::mutators(seed) {
+                mutator.apply(&mut tuning);
+            }
+            let today = DailyChallenge::today();
+            if today.seed == seed && ctx.daily_history.get_score(&today.date).is_none() {
+                daily_date = Some(today.date);
+            }
+        } else {
+            ctx.settings.difficulty.apply(&mut tuning);
+            if ctx.settings.adaptive_difficulty {
+                director = Some(Director::new());
+            }
+        }
+        let player = Player::new(
+            ctx,
+            PlayerControls::new(&controls::SOLO_KEYS, 0),
+            spawn_position(ctx, 0, 1),
+            &tuning
+        );
+        let ghost = ctx.settings.ghost
+            .then(|| GhostRun::load(mode, seed))
+            .flatten()
+            .map(|run| Ghost::new(ctx, run));
+        PlayingScene {
+            mode,
+            tuning,
+            seed,
+            daily_date,
+            director,
+            rng: Rng::new(seed),
+            accumulator: 0.0,
+            steps: 0,
+            players: vec![player],
+            bullets: vec![],
+            enemy_bullets: vec![],
+            asteroids: vec![],
+            enemies: vec![],
+            explosions: vec![],
+            stats: RunStats {
+                player_scores: vec![0],
+                ..Default::default()
+            },
+            score_popups: ScorePopups::new(),
+            lives_rule: ctx.settings.coop_lives,
+            team_lives: tuning.lives,
+            game_over: false,
+            recording: Some(GhostRun::new(mode, seed)),
+            ghost,
+        }
+    }
+
+    fn read_input(&mut self, ctx: &GameContext) {
+        for player in &mut self.players {
+            let input = player.controls.read(&ctx.navigation);
+            player.movement = input.movement;
+            player.fire_pressed |= input.fire;
+        }
+    }
+
+    fn handle_input(&mut self, ctx: &mut GameContext, delta_time: f32) {
+        let playfield = vec2(ctx.viewport.width(), ctx.viewport.height());
+        for (index, player) in self.players.iter_mut().enumerate() {
+            let fire = std::mem::take(&mut player.fire_pressed);
+            if player.out {
+                continue;
+            }
+            let object = &mut player.object;
+            steer(object, player.movement, player.knockback, delta_time, playfield);
+            player.knockback *= KNOCKBACK_DAMPING.powf(delta_time);
+            ctx.direction_modifier += 0.05 * player.movement.x * delta_time;
+
+            if fire && self.mode.can_shoot() {
+                let current_time = self.stats.time_survived;
+                if current_time - player.last_shot_time >= self.tuning.reload_time {
+                    self.bullets.push((
+                        GameObject {
+                            width: game_mode::BULLET_SIZE.x,
+                            height: game_mode::BULLET_SIZE.y,
+                            x: object.x,
+                            y: object.y - 24.0,
+                            speed: object.speed * 2.0,
+                            rotation: 0.0,
+                            collided: false,
+                            texture_id: resource_manager::constants::BULLET_TEX_ID.to_string(),
+                            sprite: ctx.resource_manager
+                                .get_animated_sprite(resource_manager::constants::BULLET_TEX_ID).unwrap(),
+                            animator: Some(bullet_animator()),
+                        },
+                        index
+                    ));
+                    ctx.sound_manager.play_once(
+                        resource_manager::constants::LASER_SOUND,
+                        SoundParams {
+                            volume: 0.6,
+                            pitch_variation: 0.08,
+                            priority: Priority::Low,
+                            ..Default::default()
+                        }.at_x(object.x, ctx.viewport.width())
+                    ).unwrap_or_else(|err| warn!("{}", err));
+                    ctx.camera_effects.kick(vec2(0.0, -4.0));
+                    ctx.achievements.record(GameEvent::Shot);
+                    self.stats.shots += 1;
+                    if let Some(director) = &mut self.director {
+                        director.record_shot();
+                    }
+                    player.last_shot_time = current_time;
+                }
+            }
+        }
+    }
+
+    /// Lets the second player drop in with their fire key or gamepad.
+    fn handle_join(&mut self, ctx: &GameContext) {
+        if self.players.len() >= MAX_PLAYERS || self.game_over {
+            return;
+        }
+        let index = self.players.len();
+        let key_pressed = controls::PLAYER_TWO_KEYS.fire.iter().any(|key| is_key_pressed(*key));
+        let gamepad_pressed = ctx.navigation.gamepad(index).is_some_and(|gamepad| gamepad.fire);
+        if !key_pressed && !gamepad_pressed {
+            return;
+        }
+        // The first player gives up the other half of the keyboard
+        self.players[0].controls.set_keys(&controls::PLAYER_ONE_KEYS);
+        self.players.push(Player::new(
+            ctx,
+            PlayerControls::new(&controls::PLAYER_TWO_KEYS, index),
+            spawn_position(ctx, index, index + 1),
+            &self.tuning
+        ));
+        self.stats.player_scores.push(0);
+        // Ghosts race solo runs only
+        self.recording = None;
+        self.ghost = None;
+        if self.lives_rule == LivesRule::Shared {
+            self.team_lives += self.tuning.lives;
+        }
+    }
+
+    fn lives(&self, index: usize) -> u32 {
+        match self.lives_rule {
+            LivesRule::Shared => self.team_lives,
+            LivesRule::Separate => self.players[index].lives,
+        }
+    }
+
+    /// Numbers the run is played with right now, adjusted by the director.
+    fn current_tuning(&self) -> Tuning {
+        self.director
+            .as_ref()
+            .map_or(self.tuning, |director| director.adjust(&self.tuning))
+    }
+
+    /// An enemy or its shot hit the player, returns whether it cost a life.
+    fn player_damaged(&mut self, ctx: &mut GameContext, index: usize, damage: u32) -> bool {
+        if let Some(director) = &mut self.director {
+            director.record_damage(damage);
+        }
+        let player = &mut self.players[index];
+        if !damage_ship(&mut player.health, &mut player.combo, damage) {
+            player.invulnerable = DAMAGE_INVULNERABILITY_SECONDS;
+            ctx.camera_effects.add_trauma(0.5);
+            return false;
+        }
+        player.health = self.tuning.ship_health;
+        if let Some(director) = &mut self.director {
+            director.record_death();
+        }
+        self.player_hit(ctx, index);
+        true
+    }
+
+    /// Takes a life from the player, who respawns if there are lives left.
+    fn player_hit(&mut self, ctx: &mut GameContext, index: usize) {
+        let respawn_position = spawn_position(ctx, index, self.players.len());
+        let explosion_texture = ctx.resource_manager
+            .get_texture(resource_manager::constants::EXPLOSION_TEX_ID).unwrap();
+        let player = &mut self.players[index];
+        let position = vec2(player.object.x, player.object.y);
+        ctx.sound_manager.play_once(
+            resource_manager::constants::EXPLOSION_SOUND,
+            SoundParams {
+                priority: Priority::Critical,
+                ..Default::default()
+            }.at_x(position.x, ctx.viewport.width())
+        ).unwrap_or_else(|err| warn!("{}", err));
+        self.explosions.push((
+            Emitter::new(EmitterConfig {
+                amount: player.object.height.round() as u32,
+                texture: Some(explosion_texture.clone()),
+                ..particle_explosion()
+            }),
+            position
+        ));
+        player.combo.break_chain();
+        ctx.camera_effects.add_trauma(0.8);
+        ctx.camera_effects.hit_stop(0.15);
+
+        let lives = match self.lives_rule {
+            LivesRule::Shared => &mut self.team_lives,
+            LivesRule::Separate => &mut player.lives,
+        };
+        *lives = lives.saturating_sub(1);
+        if *lives > 0 {
+            player.object.x = respawn_position.x;
+            player.object.y = respawn_position.y;
+            player.knockback = Vec2::ZERO;
+            player.invulnerable = RESPAWN_INVULNERABILITY_SECONDS;
+        } else if self.lives_rule == LivesRule::Shared {
+            for player in &mut self.players {
+                player.out = true;
+            }
+        } else {
+            player.out = true;
+        }
+        for player in self.players.iter_mut().filter(|player| player.out) {
+            player.engine.config.emitting = false;
+        }
+        self.game_over = self.players.iter().all(|player| player.out);
+    }
+
+    fn draw_player_hud(&self, ctx: &GameContext, index: usize, anchor: Anchor) {
+        let player = &self.players[index];
+        let coop = self.players.len() > 1;
+        let localization = &ctx.localization;
+        let mut position = ctx.viewport.anchor(anchor, vec2(10.0, 25.0));
+        let mut next_line = |text: &str| {
+            let area = ctx.typography.draw(text, typography::HUD, position, anchor);
+            position.y = area.bottom() + 8.0;
+            area
+        };
+        if coop {
+            next_line(&localization.format("hud.player", &[("number", &(index + 1))]));
+        }
+        next_line(&localization.format("hud.score", &[("score", &self.stats.player_scores[index])]));
+        if player.out {
+            next_line(localization.get("hud.out"));
+        } else if !coop || self.lives_rule == LivesRule::Separate {
+            next_line(&localization.format("hud.lives", &[("lives", &self.lives(index))]));
+        }
+        if index == 0
+            && let Some(ghost) = &self.ghost
+        {
+            let delta = self.stats.score as i64 - ghost.run.score_at(self.steps) as i64;
+            next_line(&localization.format("hud.ghost", &[("delta", &format!("{:+}", delta))]));
+        }
+        if player.combo.chain() > 0 {
+            let combo_area = next_line(
+                &localization.format("hud.combo", &[("multiplier", &player.combo.multiplier())])
+            );
+            draw_rectangle(
+                combo_area.x,
+                combo_area.bottom() + 6.0,
+                COMBO_BAR_SIZE.x * player.combo.time_left(),
+                COMBO_BAR_SIZE.y,
+                GOLD
+            );
+        }
+    }
+
+    /// Best score to beat, daily challenges compare against all earlier days.
+    fn high_score(&self, ctx: &GameContext) -> u32 {
+        match self.mode {
+            GameMode::Daily => ctx.daily_history.best(),
+            _ => ctx.high_scores.best(self.mode),
+        }
+    }
+
+    /// Enters the score of the scored daily run, once it's over.
+    fn record_daily(&mut self, ctx: &mut GameContext) {
+        if let Some(date) = self.daily_date.take() {
+            ctx.daily_history.record(&date, self.stats.score);
+            ctx.daily_history.save();
+        }
+    }
+
+    /// Pays every player still flying for the second they survived.
+    fn score_survival(&mut self, ctx: &mut GameContext) {
+        let points = self.tuning.points(self.mode.points_per_second());
+        if points == 0 {
+            return;
+        }
+        for (index, player) in self.players.iter().enumerate() {
+            if !player.out {
+                self.stats.score += points;
+                self.stats.player_scores[index] += points;
+            }
+        }
+        ctx.achievements.record(GameEvent::Scored(self.stats.score));
+    }
+
+    /// Spawns a wave picked from the wave set, its enemies share their size,
+    /// speed and movement so a formation keeps its shape.
+    fn spawn_wave(&mut self, resource_manager: &ResourceManager, playfield: Vec2) {
+        let tuning = self.current_tuning();
+        let waves = resource_manager.get_waves();
+        let wave = waves.pick(&mut self.rng);
+        let archetype = match wave.archetype {
+            Some(index) => index,
+            None => self.rng.gen_index(waves.archetypes.len()),
+        };
+        let movement = wave.movement.as_ref().unwrap_or(&waves.archetypes[archetype].movement);
+        let enemy_frame = resource_manager.get_animated_sprite(&waves.archetypes[archetype].texture).unwrap().frame();
+        let size_mult = self.rng.gen_range(3.0, 5.0) * tuning.enemy_size;
+        let enemy_width = enemy_frame.dest_size.x * size_mult;
+        let enemy_height = enemy_frame.dest_size.y * size_mult;
+        let speed = self.rng.gen_range(300.0, 400.0) * tuning.enemy_speed;
+        let slots = wave.formation.map_or(vec![Vec2::ZERO], |formation| {
+            formation.slots(wave.count, enemy_width.max(enemy_height) * 1.2)
+        });
+        let origin = movement.start(playfield).unwrap_or_else(|| {
+            // The whole formation starts within the playfield
+            let left = slots.iter().map(|slot| slot.x).fold(0.0, f32::min);
+            let right = slots.iter().map(|slot| slot.x).fold(0.0, f32::max);
+            vec2(
+                self.rng.gen_range(enemy_width / 2.0 - left, playfield.x - enemy_width / 2.0 - right),
+                -enemy_height
+            )
+        });
+        for slot in slots {
+            self.spawn_enemy(resource_manager, archetype, movement.clone(), origin + slot, size_mult, speed);
+        }
+    }
+
+    fn spawn_enemy(
+        &mut self,
+        resource_manager: &ResourceManager,
+        archetype: usize,
+        movement: Movement,
+        position: Vec2,
+        size_mult: f32,
+        speed: f32
+    ) {
+        let tuning = self.current_tuning();
+        let definition = &resource_manager.get_waves().archetypes[archetype];
+        let enemy_sprite = resource_manager.get_animated_sprite(&definition.texture).unwrap();
+        let enemy_frame = enemy_sprite.frame();
+        let object = GameObject {
+            width: enemy_frame.dest_size.x * size_mult,
+            height: enemy_frame.dest_size.y * size_mult,
+            speed,
+            x: position.x,
+            y: position.y,
+            rotation: 0.0,
+            collided: false,
+            texture_id: definition.texture.clone(),
+            animator: (definition.behaviour == Some(Behaviour::Strafer))
+                .then(|| strafer_animator(enemy_sprite.animation())),
+            sprite: enemy_sprite,
+        };
+        self.enemies.push(Enemy {
+            object,
+            archetype,
+            health: tuning.enemy_health + definition.behaviour.map_or(0, Behaviour::extra_health),
+            flash: 0.0,
+            movement,
+            brain: definition.behaviour.map(Brain::new),
+            origin: position,
+            age: 0.0,
+        });
+    }
+
+    /// Carries out what an enemy of the archetype at `position` decided to do.
+    fn enemy_action(&mut self, resource_manager: &ResourceManager, action: Action, archetype: usize, position: Vec2, speed: f32) {
+        match action {
+            Action::Fire => {
+                let mut sprite = resource_manager
+                    .get_animated_sprite(resource_manager::constants::BULLET_TEX_ID).unwrap();
+                sprite.set_animation("bolt");
+                self.enemy_bullets.push(GameObject {
+                    width: 24.0,
+                    height: 24.0,
+                    speed: ENEMY_BULLET_SPEED,
+                    x: position.x,
+                    y: position.y,
+                    rotation: 0.0,
+                    collided: false,
+                    texture_id: resource_manager::constants::BULLET_TEX_ID.to_string(),
+                    sprite,
+                    animator: None,
+                });
+            }
+            Action::Release => {
+                let archetypes = &resource_manager.get_waves().archetypes;
+                if let Some(released) = archetypes[archetype].releases {
+                    let size_mult = RELEASED_SIZE * self.tuning.enemy_size;
+                    let movement = archetypes[released].movement.clone();
+                    self.spawn_enemy(resource_manager, released, movement, position, size_mult, speed);
+                }
+            }
+        }
+    }
+
+    /// Takes a hit off the asteroid and breaks it into fragments when it has
+    /// none left. Points go to the player who shot it, if anyone did.
+    fn hit_asteroid(&mut self, ctx: &mut GameContext, index: usize, shooter: Option<usize>) {
+        let asteroid = &mut self.asteroids[index];
+        asteroid.health = asteroid.health.saturating_sub(1);
+        if asteroid.health > 0 {
+            asteroid.flash = HIT_FLASH_SECONDS;
+            ctx.camera_effects.add_trauma(0.05);
+            return;
+        }
+        asteroid.object.collided = true;
+        let position = asteroid.position();
+        let size = asteroid.size;
+        let fragments = asteroid.split(ctx.resource_manager);
+        self.explosions.push(explosion(ctx, &asteroid.object));
+        self.asteroids.extend(fragments);
+        ctx.sound_manager.play_once(
+            resource_manager::constants::EXPLOSION_SOUND,
+            SoundParams {
+                volume: 0.6,
+                pitch_variation: 0.12,
+                ..Default::default()
+            }.at_x(position.x, ctx.viewport.width())
+        ).unwrap_or_else(|err| warn!("{}", err));
+        ctx.camera_effects.add_trauma(0.1);
+
+        if let Some(owner) = shooter {
+            let combo = &mut self.players[owner].combo;
+            let points = combo.register_kill(self.tuning.points(size.points()));
+            self.stats.score += points;
+            self.stats.player_scores[owner] += points;
+            self.stats.max_combo = self.stats.max_combo.max(combo.multiplier());
+            self.score_popups.spawn(position, points);
+            ctx.achievements.record(GameEvent::Scored(self.stats.score));
+            ctx.achievements.record(GameEvent::Combo(combo.multiplier()));
+        }
+    }
+
+    /// Advances the run by one step, returns whether the first player was hit.
+    fn step(&mut self, ctx: &mut GameContext) -> bool {
+        let delta_time = STEP_TIME;
+        self.handle_input(ctx, delta_time);
+        ctx.achievements.record(GameEvent::Tick(delta_time));
+        self.stats.time_survived += delta_time;
+        if let Some(ghost) = &mut self.ghost {
+            ghost.step(ctx, self.steps);
+        }
+        self.steps += 1;
+        if let Some(director) = &mut self.director {
+            director.update(delta_time);
+        }
+        if self.mode.time_limit().is_some_and(|limit| self.stats.time_survived >= limit) {
+            self.game_over = true;
+            return false;
+        }
+        if self.steps.is_multiple_of(TICK_RATE) {
+            self.score_survival(ctx);
+        }
+
+        // Ganerate a new wave, keeping the number of enemies to the spawn chance
+        let playfield = vec2(ctx.viewport.width(), ctx.viewport.height());
+        let wave_chance = self.current_tuning().spawn_chance / ctx.resource_manager.get_waves().average_count();
+        if self.rng.chance(wave_chance) {
+            self.spawn_wave(ctx.resource_manager, playfield);
+        }
+        let tuning = self.current_tuning();
+        if self.rng.chance(tuning.asteroid_chance) {
+            let asteroid = Asteroid::spawn(ctx.resource_manager, &mut self.rng, playfield.x, tuning.enemy_speed);
+            self.asteroids.push(asteroid);
+        }
+
+        // Movement
+        let targets: Vec<Vec2> = self.players
+            .iter()
+            .filter(|player| !player.out)
+            .map(|player| vec2(player.object.x, player.object.y))
+            .collect();
+        let mut actions = vec![];
+        for enemy in &mut self.enemies {
+            if let Some(action) = enemy.fly(delta_time, &targets, playfield) {
+                let position = vec2(enemy.object.x, enemy.object.y + enemy.object.height / 2.0);
+                actions.push((action, enemy.archetype, position, enemy.object.speed));
+            }
+            enemy.flash = (enemy.flash - delta_time).max(0.0);
+        }
+        for (action, archetype, position, speed) in actions {
+            self.enemy_action(ctx.resource_manager, action, archetype, position, speed);
+        }
+        for bullet in &mut self.enemy_bullets {
+            bullet.y += bullet.speed * delta_time;
+            bullet.animate(delta_time);
+        }
+        for asteroid in &mut self.asteroids {
+            asteroid.update(delta_time);
+        }
+        for (bullet, _) in &mut self.bullets {
+            bullet.y -= bullet.speed * delta_time;
+        }
+
+        for player in &mut self.players {
+            player.combo.update(delta_time);
+            player.invulnerable = (player.invulnerable - delta_time).max(0.0);
+        }
+
+        // Animation
+        for player in &mut self.players {
+            player.object.animate(delta_time);
+        }
+        let mut actions = vec![];
+        for enemy in &mut self.enemies {
+            if let Some(action) = enemy.animate(delta_time) {
+                let position = vec2(enemy.object.x, enemy.object.y + enemy.object.height / 2.0);
+                actions.push((action, enemy.archetype, position, enemy.object.speed));
+            }
+        }
+        for (action, archetype, position, speed) in actions {
+            self.enemy_action(ctx.resource_manager, action, archetype, position, speed);
+        }
+        for (bullet, _) in &mut self.bullets {
+            bullet.animate(delta_time);
+        }
+
+        // Check for collisions
+        let mut first_player_hit = false;
+        // Asteroids bounce off every ship, only those that aren't invulnerable take damage
+        let enemy_damage = self.current_tuning().enemy_damage;
+        for index in 0..self.players.len() {
+            let player = &mut self.players[index];
+            if player.out {
+                continue;
+            }
+            let Some(asteroid_index) = self.asteroids
+                .iter()
+                .position(|asteroid| !asteroid.object.collided && player.object.collides_with(&asteroid.object))
+            else {
+                continue;
+            };
+            let ship_velocity = player.movement * player.object.speed + player.knockback;
+            let knockback = self.asteroids[asteroid_index].bounce(vec2(player.object.x, player.object.y), ship_velocity);
+            // Already drifting apart
+            if knockback == Vec2::ZERO {
+                continue;
+            }
+            player.knockback += knockback;
+            if player.invulnerable > 0.0 {
+                continue;
+            }
+            let damage = self.asteroids[asteroid_index].damage(enemy_damage);
+            self.hit_asteroid(ctx, asteroid_index, None);
+            if self.player_damaged(ctx, index, damage) && index == 0 {
+                first_player_hit = true;
+            }
+        }
+        for index in 0..self.players.len() {
+            let player = &self.players[index];
+            if player.out || player.invulnerable > 0.0 {
+                continue;
+            }
+            // The enemy goes down with the ship
+            if let Some(enemy) = self.enemies
+                .iter_mut()
+                .find(|enemy| !enemy.object.collided && player.object.collides_with(&enemy.object))
+            {
+                enemy.object.collided = true;
+                let enemy_explosion = explosion(ctx, &enemy.object);
+                let damage = self.current_tuning().enemy_damage;
+                if !self.player_damaged(ctx, index, damage) {
+                    self.explosions.push(enemy_explosion);
+                } else if index == 0 {
+                    first_player_hit = true;
+                }
+                continue;
+            }
+            if let Some(bullet) = self.enemy_bullets
+                .iter_mut()
+                .find(|bullet| !bullet.collided && player.object.collides_with(bullet))
+            {
+                bullet.collided = true;
+                if self.player_damaged(ctx, index, ENEMY_BULLET_DAMAGE) && index == 0 {
+                    first_player_hit = true;
+                }
+            }
+        }
+        if self.game_over {
+            return first_player_hit;
+        }
+        // Shots don't go through asteroids
+        for index in 0..self.bullets.len() {
+            let (bullet, owner) = &mut self.bullets[index];
+            if bullet.collided {
+                continue;
+            }
+            let Some(asteroid_index) = self.asteroids
+                .iter()
+                .position(|asteroid| !asteroid.object.collided && bullet.collides_with(&asteroid.object))
+            else {
+                continue;
+            };
+            bullet.collided = true;
+            let owner = *owner;
+            self.stats.hits += 1;
+            if let Some(director) = &mut self.director {
+                director.record_hit();
+            }
+            self.hit_asteroid(ctx, asteroid_index, Some(owner));
+        }
+        for Enemy { object: enemy, health, flash, brain, .. } in self.enemies.iter_mut() {
+            for (bullet, owner) in self.bullets.iter_mut() {
+                if !bullet.collided && !enemy.collided && bullet.collides_with(enemy) {
+                    bullet.collided = true;
+                    self.stats.hits += 1;
+                    if let Some(director) = &mut self.director {
+                        director.record_hit();
+                    }
+                    *health = health.saturating_sub(1);
+                    if *health > 0 {
+                        *flash = HIT_FLASH_SECONDS;
+                        if let Some(brain) = brain {
+                            brain.damaged();
+                        }
+                        ctx.camera_effects.add_trauma(0.05);
+                        continue;
+                    }
+                    enemy.collided = true;
+                    let combo = &mut self.players[*owner].combo;
+                    let points = combo.register_kill(self.tuning.points(enemy.height.round() as u32));
+                    self.stats.score += points;
+                    self.stats.player_scores[*owner] += points;
+                    self.stats.record_kill(&enemy.texture_id);
+                    self.stats.max_combo = self.stats.max_combo.max(combo.multiplier());
+                    self.score_popups.spawn(vec2(enemy.x, enemy.y), points);
+                    ctx.achievements.record(GameEvent::Kill { big: enemy.height >= BIG_KILL_HEIGHT });
+                    ctx.achievements.record(GameEvent::Scored(self.stats.score));
+                    ctx.achievements.record(GameEvent::Combo(combo.multiplier()));
+
+                    // Start new explosion
+                    self.explosions.push(explosion(ctx, enemy));
+                    play_enemy_explosion(&mut ctx.sound_manager, enemy.x, ctx.viewport.width());
+
+                    if enemy.height >= BIG_KILL_HEIGHT {
+                        ctx.camera_effects.add_trauma(0.35);
+                        ctx.camera_effects.hit_stop(0.05);
+                    } else {
+                        ctx.camera_effects.add_trauma(0.15);
+                    }
+                }
+            }
+        }
+
+        // Remove shapes outside of the playfield
+        let playfield_height = ctx.viewport.height();
+        self.enemies.retain(|enemy| enemy.object.y < playfield_height + enemy.object.height);
+        self.bullets.retain(|(bullet, _)| bullet.y > 0.0 - bullet.height);
+        self.enemy_bullets.retain(|bullet| bullet.y < playfield_height + bullet.height);
+        self.asteroids.retain(|asteroid| !asteroid.object.collided && !asteroid.is_lost(playfield));
+
+        // Remove collided shaped
+        self.enemies.retain(|enemy| !enemy.object.collided);
+        self.bullets.retain(|(bullet, _)| !bullet.collided);
+        self.enemy_bullets.retain(|bullet| !bullet.collided);
+        first_player_hit
+    }
+}
+
+impl Scene for PlayingScene {
+    fn on_enter(&mut self, ctx: &mut GameContext) {
+        ctx.achievements.record(GameEvent::RunStarted);
+        // The attempt of the day is used up as soon as it starts, even if the
+        // game doesn't get to record its score
+        if let Some(date) = &self.daily_date {
+            ctx.daily_history.record(date, 0);
+            ctx.daily_history.save();
+        }
+        self.on_resume(ctx);
+    }
+
+    fn on_exit(&mut self, ctx: &mut GameContext) {
+        // Leaving early keeps the score reached so far
+        self.record_daily(ctx);
+        ctx.achievements.save();
+        ctx.lifetime_stats.add_run(&self.stats);
+        ctx.lifetime_stats.save();
+    }
+
+    fn on_resume(&mut self, ctx: &mut GameContext) {
+        ctx.sound_manager.start_playing(resource_manager::constants::THEME_MUSIC, 0.7)
+            .unwrap_or_else(|err| warn!("{}", err));
+        ctx.sound_manager.set_volume(resource_manager::constants::THEME_MUSIC, 0.7)
+            .unwrap_or_else(|err| warn!("{}", err));
+    }
+
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        if ctx.camera_effects.is_frozen() {
+            return SceneCommand::None;
+        }
+        if self.game_over {
+            if let Some(recording) = self.recording.take() {
+                recording.save_if_best();
+            }
+            let scored = self.mode != GameMode::Daily || self.daily_date.is_some();
+            self.record_daily(ctx);
+            return SceneCommand::Push(
+                Box::new(GameOverScene::new(self.stats.clone(), self.mode, self.seed, scored)),
+                Transition::Cut
+            );
+        }
+        if ctx.navigation.pressed(NavAction::Back) {
+            return SceneCommand::Push(Box::new(PauseScene::new()), Transition::Cut);
+        }
+
+        let delta_time = get_frame_time();
+        self.read_input(ctx);
+        self.handle_join(ctx);
+        self.accumulator = (self.accumulator + delta_time).min(STEP_TIME * MAX_STEPS_PER_FRAME as f32);
+        while self.accumulator >= STEP_TIME {
+            self.accumulator -= STEP_TIME;
+            let (movement, fire) = (self.players[0].movement, self.players[0].fire_pressed);
+            // The knockback the step steers with, before it decays or a bounce adds to it
+            let knockback = self.players[0].knockback;
+            let hit = self.step(ctx);
+            if let Some(recording) = &mut self.recording {
+                recording.record(GhostFrame::new(movement, fire, knockback, hit, self.stats.score));
+            }
+            if self.game_over || ctx.camera_effects.is_frozen() {
+                break;
+            }
+        }
+
+        self.score_popups.update(delta_time);
+        // Remove the old explosions
+        self.explosions.retain(|(explosion, _)| explosion.config.emitting);
+
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, ctx: &mut GameContext) {
+        if let Some(ghost) = &self.ghost
+            && ghost.is_visible(self.steps)
+        {
+            ghost.object.draw_tinted(ctx.resource_manager, GHOST_TINT);
+        }
+
+        // Draw players
+        for player in &mut self.players {
+            player.engine.draw(vec2(
+                    player.object.x,
+                    player.object.y + player.object.height / 3.0
+            ));
+            if player.is_visible() {
+                player.object.draw(ctx.resource_manager);
+            }
+            if !player.out && player.health < self.tuning.ship_health {
+                let health = player.health as f32 / self.tuning.ship_health as f32;
+                draw_rectangle(
+                    player.object.x - HEALTH_BAR_SIZE.x / 2.0,
+                    player.object.y + player.object.height / 2.0 + 6.0,
+                    HEALTH_BAR_SIZE.x * health,
+                    HEALTH_BAR_SIZE.y,
+                    ORANGE
+                );
+            }
+        }
+
+        // Draw bullets
+        for (bullet, _) in &self.bullets {
+            bullet.draw(ctx.resource_manager);
+        }
+
+        // Draw asteroids
+        for asteroid in &self.asteroids {
+            let tint = if asteroid.flash > 0.0 { HIT_FLASH_TINT } else { WHITE };
+            asteroid.object.draw_tinted(ctx.resource_manager, tint);
+        }
+
+        // Draw enemies
+        for enemy in &self.enemies {
+            enemy.object.draw_tinted(ctx.resource_manager, enemy.tint());
+            // The shield holds as long as the enemy has hits to spare
+            if enemy.brain.as_ref().is_some_and(|brain| brain.behaviour() == Behaviour::Shielded)
+                && enemy.health > self.tuning.enemy_health
+            {
+                let radius = enemy.object.width.max(enemy.object.height) * 0.6;
+                draw_circle_lines(enemy.object.x, enemy.object.y, radius, 2.0, SHIELD_COLOR);
+            }
+        }
+        for bullet in &self.enemy_bullets {
+            bullet.draw_tinted(ctx.resource_manager, ENEMY_BULLET_TINT);
+        }
+        for (explosion, coords) in self.explosions.iter_mut() {
+            explosion.draw(*coords);
+        }
+        self.score_popups.draw(&ctx.typography);
+
+        let high_score_area = ctx.typography.draw(
+            &ctx.localization.format("hud.high_score", &[
+                ("score", &self.high_score(ctx).max(self.stats.score))
+            ]),
+            typography::HUD,
+            ctx.viewport.anchor(Anchor::TopCenter, vec2(0.0, 25.0)),
+            Anchor::TopCenter
+        );
+        let mut center_line = vec2(high_score_area.center().x, high_score_area.bottom() + 8.0);
+        if let Some(limit) = self.mode.time_limit() {
+            let area = ctx.typography.draw(
+                &ctx.localization.format("hud.time_left", &[
+                    ("time", &stats::format_duration((limit - self.stats.time_survived).ceil()))
+                ]),
+                typography::HUD,
+                center_line,
+                Anchor::TopCenter
+            );
+            center_line.y = area.bottom() + 8.0;
+        }
+        if self.mode == GameMode::Daily && self.daily_date.is_none() {
+            let area = ctx.typography.draw(ctx.localization.get("hud.practice"), typography::HUD, center_line, Anchor::TopCenter);
+            center_line.y = area.bottom() + 8.0;
+        }
+        if self.players.len() > 1 && self.lives_rule == LivesRule::Shared {
+            ctx.typography.draw(
+                &ctx.localization.format("hud.team_lives", &[("lives", &self.team_lives)]),
+                typography::HUD,
+                center_line,
+                Anchor::TopCenter
+            );
+        }
+        self.draw_player_hud(ctx, 0, Anchor::TopLeft);
+        if self.players.len() > 1 {
+            self.draw_player_hud(ctx, 1, Anchor::TopRight);
+        } else {
+            ctx.typography.draw(
+                ctx.localization.get("hud.join"),
+                typography::HUD,
+                ctx.viewport.anchor(Anchor::TopRight, vec2(10.0, 25.0)),
+                Anchor::TopRight
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use super::*;
+    use crate::animation::{AnimatedSprite, SpriteSheet};
+    use crate::audio_backend::{AudioEvent, RecordingBackend};
+
+    fn strafer(position: Vec2) -> Enemy {
+        let sheet = SpriteSheet::from_aseprite_json(include_str!("../../assets/animations/enemy-medium.json")).unwrap();
+        let sprite = AnimatedSprite::new(Rc::new(sheet));
+        Enemy {
+            object: GameObject {
+                width: 64.0,
+                height: 64.0,
+                speed: 350.0,
+                x: position.x,
+                y: position.y,
+                rotation: 0.0,
+                collided: false,
+                texture_id: resource_manager::constants::ENEMY_MEDIUM_TEX_ID.to_string(),
+                animator: Some(strafer_animator(sprite.animation())),
+                sprite,
+            },
+            archetype: 0,
+            health: 1,
+            flash: 0.0,
+            movement: Movement::Straight,
+            brain: Some(Brain::new(Behaviour::Strafer)),
+            origin: position,
+            age: 0.0,
+        }
+    }
+
+    #[test]
+    fn strafer_fires_on_the_frame_event_of_its_firing_animation() {
+        let playfield = vec2(800.0, 600.0);
+        let mut enemy = strafer(vec2(400.0, 200.0));
+        let mut shots = vec![];
+        for step in 0..TICK_RATE {
+            assert_eq!(enemy.fly(STEP_TIME, &[vec2(400.0, 500.0)], playfield), None);
+            if enemy.animate(STEP_TIME) == Some(Action::Fire) {
+                shots.push(step);
+            }
+        }
+        // The brain fires half a second in, the bolt leaves a frame of the animation later
+        let [shot] = shots[..] else {
+            panic!("Expected one shot, got {:?}", shots);
+        };
+        assert!(shot > TICK_RATE / 2 && shot < TICK_RATE / 2 + 10, "Shot on step {}", shot);
+        assert_eq!(enemy.object.animator.as_ref().unwrap().state(), "fly");
+    }
+
+    #[test]
+    fn damage_that_doesnt_cost_a_life_breaks_the_combo() {
+        let mut combo = Combo::new();
+        for _ in 0..8 {
+            combo.register_kill(10);
+        }
+        let mut health = Tuning::default().ship_health;
+        assert!(!damage_ship(&mut health, &mut combo, ENEMY_BULLET_DAMAGE));
+        assert_eq!(health, Tuning::default().ship_health - ENEMY_BULLET_DAMAGE);
+        assert_eq!(combo.chain(), 0);
+        assert_eq!(combo.multiplier(), 1);
+    }
+
+    #[test]
+    fn enemy_explosion_played_once_and_panned_to_the_enemy() {
+        let mut sound_manager = SoundManager::new(RecordingBackend::new(&[resource_manager::constants::EXPLOSION_SOUND]));
+        play_enemy_explosion(&mut sound_manager, 600.0, 800.0);
+
+        let backend = sound_manager.backend();
+        assert!(backend.was_played(resource_manager::constants::EXPLOSION_SOUND));
+        let [AudioEvent::Played { id, params }] = backend.events() else {
+            panic!("Expected one played sound, got {:?}", backend.events());
+        };
+        assert_eq!(id, resource_manager::constants::EXPLOSION_SOUND);
+        assert!(!params.looped);
+        assert_eq!(params.pan, 0.5);
+        assert!((0.88..=1.12).contains(&params.pitch));
+    }
+
+    #[test]
+    fn explosions_past_the_voice_limit_stop_the_oldest() {
+        let mut backend = RecordingBackend::new(&[resource_manager::constants::EXPLOSION_SOUND]);
+        backend.set_duration(resource_manager::constants::EXPLOSION_SOUND, 60.0);
+        let mut sound_manager = SoundManager::new(backend);
+        sound_manager.set_voice_limit(resource_manager::constants::EXPLOSION_SOUND, 2);
+        for _ in 0..3 {
+            play_enemy_explosion(&mut sound_manager, 400.0, 800.0);
+        }
+
+        let backend = sound_manager.backend();
+        assert_eq!(backend.play_count(resource_manager::constants::EXPLOSION_SOUND), 3);
+        assert!(backend.events().contains(&AudioEvent::Stopped {
+            id: resource_manager::constants::EXPLOSION_SOUND.to_string(),
+            channel: Some(0),
+        }));
+    }
+}
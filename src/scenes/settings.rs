@@ -0,0 +1,141 @@
+use macroquad::prelude::*;
+use crate::menu::Layout;
+use crate::resource_manager;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::settings::LivesRule;
+use crate::viewport::ScalingMode;
+
+const WINDOW_SIZE: Vec2 = vec2(460.0, 582.0);
+
+pub struct SettingsScene;
+
+impl SettingsScene {
+    fn close(ctx: &mut GameContext) -> SceneCommand {
+        ctx.settings.save();
+        SceneCommand::Pop(Transition::Cut)
+    }
+}
+
+impl Scene for SettingsScene {
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        let mut back = false;
+        let pass_names: Vec<String> = ctx.post_processor.pass_names().map(str::to_string).collect();
+        let mut enabled: Vec<bool> = pass_names
+            .iter()
+            .map(|name| ctx.post_processor.is_enabled(name))
+            .collect();
+        let localization = &ctx.localization;
+        let languages = localization.languages();
+        let language_labels: Vec<&str> = languages.iter().map(|(_, name)| *name).collect();
+        let mut language_index = languages
+            .iter()
+            .position(|(code, _)| *code == localization.language())
+            .unwrap_or(0);
+        let theme_names: Vec<String> = resource_manager::constants::THEMES
+            .iter()
+            .map(|theme| localization.get(&format!("settings.theme.{}", theme)).to_string())
+            .collect();
+        let theme_labels: Vec<&str> = theme_names.iter().map(String::as_str).collect();
+        let mut theme_index = resource_manager::constants::THEMES
+            .iter()
+            .position(|theme| *theme == ctx.window_manager.theme())
+            .unwrap_or(0);
+        let mode_names: Vec<String> = ScalingMode::ALL
+            .iter()
+            .map(|mode| localization.get(&format!("settings.scaling.{}", mode)).to_string())
+            .collect();
+        let mode_labels: Vec<&str> = mode_names.iter().map(String::as_str).collect();
+        let pass_labels: Vec<String> = pass_names
+            .iter()
+            .map(|name| localization.get(&format!("settings.post.{}", name)).to_string())
+            .collect();
+        let rule_names: Vec<String> = LivesRule::ALL
+            .iter()
+            .map(|rule| localization.get(&format!("settings.coop_lives.{}", rule)).to_string())
+            .collect();
+        let rule_labels: Vec<&str> = rule_names.iter().map(String::as_str).collect();
+        let mut rule_index = LivesRule::ALL
+            .iter()
+            .position(|rule| *rule == ctx.settings.coop_lives)
+            .unwrap_or(0);
+        let mut shake_intensity = ctx.settings.shake_intensity;
+        let mut reduced_motion = ctx.settings.reduced_motion;
+        let mut ghost = ctx.settings.ghost;
+        let mut mode_index = ScalingMode::ALL
+            .iter()
+            .position(|mode| *mode == ctx.settings.scaling_mode)
+            .unwrap_or(0);
+
+        let window_pos = vec2(
+            screen_width() / 2.0 - WINDOW_SIZE.x / 2.0,
+            screen_height() / 2.0 - WINDOW_SIZE.y / 2.0
+        );
+        ctx.window_manager.menu(
+            "settings_window",
+            window_pos,
+            WINDOW_SIZE,
+            Layout::vertical(Rect::new(20.0, 10.0, 420.0, 542.0), 6.0),
+            &ctx.navigation,
+            |menu| {
+                menu.ui().label(vec2(130.0, -34.0), localization.get("settings.title"));
+                menu.dropdown(localization.get("settings.language"), &language_labels, &mut language_index);
+                menu.dropdown(localization.get("settings.theme"), &theme_labels, &mut theme_index);
+                menu.dropdown(localization.get("settings.scaling"), &mode_labels, &mut mode_index);
+                menu.dropdown(localization.get("settings.coop_lives"), &rule_labels, &mut rule_index);
+                for (label, enabled) in pass_labels.iter().zip(enabled.iter_mut()) {
+                    menu.toggle(label, enabled);
+                }
+                menu.slider(localization.get("settings.shake"), &mut shake_intensity, 0.0..1.0, 0.1);
+                menu.toggle(localization.get("settings.reduced_motion"), &mut reduced_motion);
+                menu.toggle(localization.get("settings.ghost"), &mut ghost);
+                if menu.small_button(localization.get("settings.back")) || menu.back() {
+                    back = true;
+                }
+            }
+        );
+        ctx.window_manager.move_window(
+            ctx.window_manager.get_window_id("settings_window").unwrap(),
+            window_pos
+        );
+
+        let mut skin_changed = false;
+        let language = languages[language_index].0;
+        if language != ctx.localization.language() && ctx.localization.set_language(language) {
+            ctx.settings.language = language.to_string();
+            ctx.typography.set_fallback_font(ctx.localization.fallback_font());
+            skin_changed = true;
+        }
+        let theme = resource_manager::constants::THEMES[theme_index];
+        if theme != ctx.window_manager.theme() && ctx.window_manager.set_theme(theme) {
+            ctx.settings.theme = theme.to_string();
+            skin_changed = true;
+        }
+        if skin_changed {
+            ctx.window_manager.configure_ui_skin(ctx.localization.fallback_font(), ctx.localization.font_scale());
+        }
+        for (name, enabled) in pass_names.iter().zip(enabled) {
+            ctx.post_processor.set_enabled(name, enabled);
+            ctx.settings.post_effects.insert(name.clone(), enabled);
+        }
+        if shake_intensity != ctx.settings.shake_intensity || reduced_motion != ctx.settings.reduced_motion {
+            ctx.settings.shake_intensity = shake_intensity;
+            ctx.settings.reduced_motion = reduced_motion;
+            ctx.camera_effects.apply_settings(&ctx.settings);
+            ctx.toasts.apply_settings(&ctx.settings);
+        }
+        let scaling_mode = ScalingMode::ALL[mode_index];
+        if scaling_mode != ctx.settings.scaling_mode {
+            ctx.settings.scaling_mode = scaling_mode;
+            ctx.viewport.set_mode(scaling_mode);
+        }
+        ctx.settings.coop_lives = LivesRule::ALL[rule_index];
+        ctx.settings.ghost = ghost;
+
+        if back {
+            return Self::close(ctx);
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, _ctx: &mut GameContext) {}
+}
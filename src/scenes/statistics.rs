@@ -0,0 +1,60 @@
+use macroquad::prelude::*;
+use crate::menu::Layout;
+use crate::scene::{GameContext, Scene, SceneCommand, Transition};
+use crate::stats;
+
+const WINDOW_SIZE: Vec2 = vec2(560.0, 500.0);
+
+/// Lifetime stats over all runs.
+pub struct StatisticsScene;
+
+impl Scene for StatisticsScene {
+    fn update(&mut self, ctx: &mut GameContext) -> SceneCommand {
+        let mut back = false;
+        let localization = &ctx.localization;
+        let lifetime = &ctx.lifetime_stats;
+        let mut rows = vec![
+            ("stats.runs".to_string(), lifetime.runs.to_string()),
+            ("stats.time_played".to_string(), stats::format_duration(lifetime.time_played)),
+            ("stats.total_score".to_string(), lifetime.total_score.to_string()),
+            ("stats.shots".to_string(), lifetime.shots.to_string()),
+            ("stats.accuracy".to_string(), format!("{}%", (lifetime.accuracy() * 100.0).round())),
+            ("stats.best_combo".to_string(), format!("x{}", lifetime.best_combo.max(1))),
+        ];
+        for (kind, _) in stats::ENEMY_KINDS {
+            rows.push((format!("stats.kills.{}", kind), lifetime.kills(kind).to_string()));
+        }
+
+        let window_pos = vec2(
+            screen_width() / 2.0 - WINDOW_SIZE.x / 2.0,
+            screen_height() / 2.0 - WINDOW_SIZE.y / 2.0
+        );
+        ctx.window_manager.menu(
+            "statistics_window",
+            window_pos,
+            WINDOW_SIZE,
+            Layout::vertical(Rect::new(20.0, 10.0, 520.0, 460.0), 4.0),
+            &ctx.navigation,
+            |menu| {
+                menu.ui().label(vec2(150.0, -34.0), localization.get("stats.title"));
+                for (key, value) in &rows {
+                    menu.info(localization.get(key), value);
+                }
+                if menu.button(localization.get("stats.back")) || menu.back() {
+                    back = true;
+                }
+            }
+        );
+        ctx.window_manager.move_window(
+            ctx.window_manager.get_window_id("statistics_window").unwrap(),
+            window_pos
+        );
+
+        if back {
+            return SceneCommand::Pop(Transition::Cut);
+        }
+        SceneCommand::None
+    }
+
+    fn draw(&mut self, _ctx: &mut GameContext) {}
+}
@@ -0,0 +1,52 @@
+use macroquad::prelude::*;
+use super::typography::{self, Typography};
+use super::viewport::Anchor;
+
+const LIFETIME_SECONDS: f32 = 0.9;
+/// Upwards drift in pixels per second
+const RISE_SPEED: f32 = 60.0;
+/// Popups start fading out after this share of their lifetime
+const FADE_START: f32 = 0.5;
+
+struct ScorePopup {
+    text: String,
+    position: Vec2,
+    age: f32,
+}
+
+/// Points floating up from where they were scored.
+pub struct ScorePopups {
+    popups: Vec<ScorePopup>,
+}
+
+impl ScorePopups {
+    pub fn new() -> Self {
+        ScorePopups {
+            popups: vec![],
+        }
+    }
+
+    pub fn spawn(&mut self, position: Vec2, points: u32) {
+        self.popups.push(ScorePopup {
+            text: format!("+{}", points),
+            position,
+            age: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for popup in &mut self.popups {
+            popup.age += delta_time;
+            popup.position.y -= RISE_SPEED * delta_time;
+        }
+        self.popups.retain(|popup| popup.age < LIFETIME_SECONDS);
+    }
+
+    pub fn draw(&self, typography: &Typography) {
+        for popup in &self.popups {
+            let progress = popup.age / LIFETIME_SECONDS;
+            let alpha = 1.0 - ((progress - FADE_START) / (1.0 - FADE_START)).clamp(0.0, 1.0);
+            typography.draw_faded(&popup.text, typography::POPUP, popup.position, Anchor::Center, alpha);
+        }
+    }
+}
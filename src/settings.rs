@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use super::game_mode::Difficulty;
+use super::localization::DEFAULT_LANGUAGE;
+use super::theme::DEFAULT_THEME;
+use super::viewport::ScalingMode;
+
+const SETTINGS_FILE: &str = "settings.dat";
+
+/// How lives are counted in a co-op game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LivesRule {
+    /// The players draw from one pool, the game ends when it runs dry
+    Shared,
+    /// Every player has their own lives and drops out without them,
+    /// the game ends once all players are out
+    Separate,
+}
+
+impl FromStr for LivesRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shared" => Ok(LivesRule::Shared),
+            "separate" => Ok(LivesRule::Separate),
+            _ => Err(format!("Unknown lives rule: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for LivesRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LivesRule::Shared => "shared",
+            LivesRule::Separate => "separate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl LivesRule {
+    pub const ALL: [LivesRule; 2] = [LivesRule::Shared, LivesRule::Separate];
+}
+
+/// User preferences, stored as `key=value` lines next to the high score.
+pub struct Settings {
+    /// Code of the language the text is shown in
+    pub language: String,
+    /// Id of the UI theme
+    pub theme: String,
+    pub scaling_mode: ScalingMode,
+    /// Multiplier for screen shake and camera kick, 0.0 turns them off
+    pub shake_intensity: f32,
+    /// Disables all camera effects
+    pub reduced_motion: bool,
+    /// Enabled state of post-processing passes by name
+    pub post_effects: HashMap<String, bool>,
+    pub coop_lives: LivesRule,
+    /// Replays the same seed after a run, racing the ghost of its best run
+    pub ghost: bool,
+    pub difficulty: Difficulty,
+    /// Lets the `Director` adjust the pacing to how the run goes
+    pub adaptive_difficulty: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            language: DEFAULT_LANGUAGE.to_string(),
+            theme: DEFAULT_THEME.to_string(),
+            scaling_mode: ScalingMode::Letterbox,
+            shake_intensity: 1.0,
+            reduced_motion: false,
+            post_effects: HashMap::new(),
+            coop_lives: LivesRule::Shared,
+            ghost: false,
+            difficulty: Difficulty::Normal,
+            adaptive_difficulty: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+        let Ok(content) = fs::read_to_string(SETTINGS_FILE) else {
+            return settings;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "language" => settings.language = value.to_string(),
+                "theme" => settings.theme = value.to_string(),
+                "scaling_mode" => {
+                    if let Ok(mode) = value.parse() {
+                        settings.scaling_mode = mode;
+                    }
+                }
+                "shake_intensity" => {
+                    if let Ok(intensity) = value.parse::<f32>() {
+                        settings.shake_intensity = intensity.clamp(0.0, 1.0);
+                    }
+                }
+                "reduced_motion" => settings.reduced_motion = value == "true",
+                "coop_lives" => {
+                    if let Ok(rule) = value.parse() {
+                        settings.coop_lives = rule;
+                    }
+                }
+                "ghost" => settings.ghost = value == "true",
+                "difficulty" => {
+                    if let Ok(difficulty) = value.parse() {
+                        settings.difficulty = difficulty;
+                    }
+                }
+                "adaptive_difficulty" => settings.adaptive_difficulty = value == "true",
+                _ => {
+                    if let Some(pass) = key.strip_prefix("post.") {
+                        settings.post_effects.insert(pass.to_string(), value == "true");
+                    }
+                }
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self) {
+        let mut content = format!("language={}\n", self.language);
+        content.push_str(&format!("theme={}\n", self.theme));
+        content.push_str(&format!("scaling_mode={}\n", self.scaling_mode));
+        content.push_str(&format!("shake_intensity={}\n", self.shake_intensity));
+        content.push_str(&format!("reduced_motion={}\n", self.reduced_motion));
+        content.push_str(&format!("coop_lives={}\n", self.coop_lives));
+        content.push_str(&format!("ghost={}\n", self.ghost));
+        content.push_str(&format!("difficulty={}\n", self.difficulty));
+        content.push_str(&format!("adaptive_difficulty={}\n", self.adaptive_difficulty));
+        let mut passes: Vec<_> = self.post_effects.iter().collect();
+        passes.sort();
+        for (pass, enabled) in passes {
+            content.push_str(&format!("post.{}={}\n", pass, enabled));
+        }
+        fs::write(SETTINGS_FILE, content).ok();
+    }
+}
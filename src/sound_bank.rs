@@ -0,0 +1,161 @@
+use macroquad::audio::{load_sound_from_bytes, Sound};
+
+// macroquad's mixer can only change the volume of a playing sound, so stereo
+// panning and pitch variation are baked into pre-rendered variants instead.
+pub const PAN_STEPS: usize = 5;
+pub const PITCH_STEPS: usize = 7;
+pub const MAX_PITCH_DEVIATION: f32 = 0.15;
+
+const OUTPUT_SAMPLE_RATE: u32 = 44100;
+
+struct Pcm {
+    sample_rate: u32,
+    channels: u16,
+    samples: Vec<i16>,
+}
+
+pub struct SoundBank {
+    variants: Vec<Sound>,
+    durations: Vec<f64>,
+}
+
+impl SoundBank {
+    pub async fn from_wav(bytes: &[u8]) -> Option<Self> {
+        let pcm = decode_wav(bytes)?;
+        let mut variants = Vec::with_capacity(PAN_STEPS * PITCH_STEPS);
+        let mut durations = Vec::with_capacity(PAN_STEPS * PITCH_STEPS);
+        for pan_step in 0..PAN_STEPS {
+            for pitch_step in 0..PITCH_STEPS {
+                let variant = render_variant(&pcm, pan_of_step(pan_step), pitch_of_step(pitch_step));
+                durations.push(variant.samples.len() as f64 / 2.0 / OUTPUT_SAMPLE_RATE as f64);
+                variants.push(load_sound_from_bytes(&encode_wav(&variant)).await.ok()?);
+            }
+        }
+        Some(SoundBank { variants, durations })
+    }
+
+    /// Index of the variant closest to the requested pan (-1.0 left .. 1.0 right)
+    /// and pitch (1.0 is the original pitch).
+    pub fn variant_index(&self, pan: f32, pitch: f32) -> usize {
+        let pan_step = ((pan.clamp(-1.0, 1.0) + 1.0) / 2.0 * (PAN_STEPS - 1) as f32).round() as usize;
+        let pitch = pitch.clamp(1.0 - MAX_PITCH_DEVIATION, 1.0 + MAX_PITCH_DEVIATION);
+        let pitch_step = ((pitch - 1.0 + MAX_PITCH_DEVIATION) / (2.0 * MAX_PITCH_DEVIATION)
+            * (PITCH_STEPS - 1) as f32).round() as usize;
+        pan_step * PITCH_STEPS + pitch_step
+    }
+
+    pub fn variant(&self, index: usize) -> &Sound {
+        &self.variants[index]
+    }
+
+    pub fn variant_count(&self) -> usize {
+        self.variants.len()
+    }
+
+    pub fn duration(&self, index: usize) -> f64 {
+        self.durations[index]
+    }
+}
+
+fn pan_of_step(step: usize) -> f32 {
+    step as f32 / (PAN_STEPS - 1) as f32 * 2.0 - 1.0
+}
+
+fn pitch_of_step(step: usize) -> f32 {
+    1.0 - MAX_PITCH_DEVIATION + step as f32 / (PITCH_STEPS - 1) as f32 * 2.0 * MAX_PITCH_DEVIATION
+}
+
+/// Reads a RIFF/WAVE file with 16 bit PCM samples.
+fn decode_wav(bytes: &[u8]) -> Option<Pcm> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut format: Option<(u16, u32)> = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = bytes.get(offset + 8..offset + 8 + chunk_size)?;
+        match chunk_id {
+            b"fmt " => {
+                let audio_format = u16::from_le_bytes(body.get(0..2)?.try_into().ok()?);
+                let channels = u16::from_le_bytes(body.get(2..4)?.try_into().ok()?);
+                let sample_rate = u32::from_le_bytes(body.get(4..8)?.try_into().ok()?);
+                let bits_per_sample = u16::from_le_bytes(body.get(14..16)?.try_into().ok()?);
+                if audio_format != 1 || bits_per_sample != 16 || channels == 0 {
+                    return None;
+                }
+                format = Some((channels, sample_rate));
+            }
+            b"data" => {
+                let (channels, sample_rate) = format?;
+                let samples = body
+                    .chunks_exact(2)
+                    .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+                    .collect();
+                return Some(Pcm { sample_rate, channels, samples });
+            }
+            _ => {}
+        }
+        // Chunks are word aligned
+        offset += 8 + chunk_size + chunk_size % 2;
+    }
+    None
+}
+
+fn encode_wav(pcm: &Pcm) -> Vec<u8> {
+    let data_size = (pcm.samples.len() * 2) as u32;
+    let block_align = pcm.channels * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&pcm.channels.to_le_bytes());
+    bytes.extend_from_slice(&pcm.sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(pcm.sample_rate * block_align as u32).to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in &pcm.samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+/// Mixes the source down to mono, resamples it for the given pitch and
+/// spreads it over two channels with an equal power pan law.
+fn render_variant(pcm: &Pcm, pan: f32, pitch: f32) -> Pcm {
+    let channels = pcm.channels as usize;
+    let mono: Vec<f32> = pcm.samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().map(|s| *s as f32).sum::<f32>() / channels as f32)
+        .collect();
+
+    let step = pitch * pcm.sample_rate as f32 / OUTPUT_SAMPLE_RATE as f32;
+    let length = (mono.len() as f32 / step) as usize;
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+    // Scaled so that a centered sound keeps its original loudness
+    let left_gain = angle.cos() * std::f32::consts::SQRT_2;
+    let right_gain = angle.sin() * std::f32::consts::SQRT_2;
+
+    let mut samples = Vec::with_capacity(length * 2);
+    for n in 0..length {
+        let position = n as f32 * step;
+        let index = position as usize;
+        let fraction = position - index as f32;
+        let current = mono[index];
+        let next = mono.get(index + 1).copied().unwrap_or(current);
+        let value = current + (next - current) * fraction;
+        samples.push((value * left_gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        samples.push((value * right_gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+    }
+
+    Pcm {
+        sample_rate: OUTPUT_SAMPLE_RATE,
+        channels: 2,
+        samples,
+    }
+}
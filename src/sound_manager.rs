@@ -1,52 +1,151 @@
 use std::collections::HashMap;
-use super::resource_manager::ResourceManager;
-use macroquad::audio::{play_sound, play_sound_once, stop_sound, set_sound_volume, PlaySoundParams};
+use macroquad::prelude::*;
+use super::audio_backend::{AudioBackend, AudioError, VoiceParams};
 
-pub struct SoundManager<'a> {
-    resource_manager: &'a ResourceManager,
+pub const MAX_VOICES_PER_SOUND: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    /// Always audible, ignores the voice limit
+    Critical,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SoundParams {
+    pub volume: f32,
+    /// -1.0 is fully left, 1.0 is fully right
+    pub pan: f32,
+    /// Maximum random deviation from the original pitch, e.g. 0.1 for ±10%
+    pub pitch_variation: f32,
+    pub priority: Priority,
+}
+
+impl Default for SoundParams {
+    fn default() -> Self {
+        SoundParams {
+            volume: 1.0,
+            pan: 0.0,
+            pitch_variation: 0.0,
+            priority: Priority::Normal,
+        }
+    }
+}
+
+impl SoundParams {
+    /// Pans the sound according to the emitter position within a playfield of the given width.
+    pub fn at_x(self, x: f32, width: f32) -> Self {
+        SoundParams {
+            pan: (x / width * 2.0 - 1.0).clamp(-1.0, 1.0),
+            ..self
+        }
+    }
+}
+
+struct ActiveVoice {
+    channel: usize,
+    started_at: f64,
+    ends_at: f64,
+    priority: Priority,
+}
+
+pub struct SoundManager<B: AudioBackend> {
+    backend: B,
     playing_state: HashMap<String, bool>,
+    voices: HashMap<String, Vec<ActiveVoice>>,
+    voice_limits: HashMap<String, usize>,
 }
 
-impl<'a> SoundManager<'a> {
-    pub fn new(resource_manager: &'a ResourceManager) -> Self {
+impl<B: AudioBackend> SoundManager<B> {
+    pub fn new(backend: B) -> Self {
         SoundManager {
-            resource_manager,
+            backend,
             playing_state: HashMap::new(),
+            voices: HashMap::new(),
+            voice_limits: HashMap::new(),
         }
     }
 
-    pub fn play_once(&self, id: &str) {
-        let sound = self.resource_manager.get_sound(id).unwrap();
-        play_sound_once(sound);
+    #[cfg(test)]
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn set_voice_limit(&mut self, id: &str, limit: usize) {
+        self.voice_limits.insert(id.to_string(), limit);
     }
 
-    pub fn start_playing(&mut self, id: &str, volume: f32) {
+    pub fn play_once(&mut self, id: &str, params: SoundParams) -> Result<(), AudioError> {
+        // Wall clock instead of `get_time`, which needs a window
+        let now = miniquad::date::now();
+        let limit = self.voice_limits.get(id).copied().unwrap_or(MAX_VOICES_PER_SOUND);
+        let voices = self.voices.entry(id.to_string()).or_default();
+        voices.retain(|voice| voice.ends_at > now);
+
+        if params.priority != Priority::Critical && voices.len() >= limit {
+            // Steal the oldest of the least important voices, unless they all outrank the new one
+            let victim = voices
+                .iter()
+                .filter(|voice| voice.priority <= params.priority)
+                .min_by(|a, b| a.priority.cmp(&b.priority).then(a.started_at.total_cmp(&b.started_at)))
+                .map(|voice| voice.channel);
+            let Some(channel) = victim else {
+                return Ok(());
+            };
+            // Stopping a channel silences every voice that shares it
+            self.backend.stop(id, Some(channel))?;
+            voices.retain(|voice| voice.channel != channel);
+        }
+
+        let pitch = 1.0 + rand::gen_range(-params.pitch_variation, params.pitch_variation);
+        let voice = self.backend.play(
+            id,
+            VoiceParams {
+                volume: params.volume,
+                pan: params.pan,
+                pitch,
+                looped: false,
+            }
+        )?;
+        voices.push(ActiveVoice {
+            channel: voice.channel,
+            started_at: now,
+            ends_at: now + voice.duration,
+            priority: params.priority,
+        });
+        Ok(())
+    }
+
+    pub fn start_playing(&mut self, id: &str, volume: f32) -> Result<(), AudioError> {
         if !self.is_playing(id) {
-            let sound = self.resource_manager.get_sound(id).unwrap();
-            play_sound(
-                sound,
-                PlaySoundParams {
-                    looped: true,
+            self.backend.play(
+                id,
+                VoiceParams {
                     volume,
+                    pan: 0.0,
+                    pitch: 1.0,
+                    looped: true,
                 }
-            );
+            )?;
             self.playing_state.insert(id.to_string(), true);
         }
+        Ok(())
     }
 
-    pub fn stop_playing(&mut self, id: &str) {
+    pub fn stop_playing(&mut self, id: &str) -> Result<(), AudioError> {
         if self.is_playing(id) {
-            let sound = self.resource_manager.get_sound(id).unwrap();
-            stop_sound(sound);
+            self.backend.stop(id, None)?;
             self.playing_state.insert(id.to_string(), false);
         }
+        Ok(())
     }
 
-    pub fn set_volume(&self, id: &str, volume: f32) {
+    pub fn set_volume(&mut self, id: &str, volume: f32) -> Result<(), AudioError> {
         if self.is_playing(id) {
-            let sound = self.resource_manager.get_sound(id).unwrap();
-            set_sound_volume(sound, volume);
+            self.backend.set_volume(id, volume)?;
         }
+        Ok(())
     }
 
     fn is_playing(&self, id:&str) -> bool {
@@ -59,3 +158,22 @@ impl<'a> SoundManager<'a> {
 
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_backend::RecordingBackend;
+
+    #[test]
+    fn unknown_sound_is_an_error() {
+        let mut sound_manager = SoundManager::new(RecordingBackend::new(&["laser"]));
+        assert_eq!(
+            sound_manager.play_once("missing", SoundParams::default()),
+            Err(AudioError::UnknownSound("missing".to_string()))
+        );
+        assert_eq!(
+            sound_manager.start_playing("missing", 1.0),
+            Err(AudioError::UnknownSound("missing".to_string()))
+        );
+        assert!(sound_manager.backend().events().is_empty());
+    }
+}
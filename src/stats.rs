@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use super::resource_manager;
+
+const STATS_FILE: &str = "stats.dat";
+
+/// Names enemies are counted under, by their texture
+pub const ENEMY_KINDS: &[(&str, &str)] = &[
+    ("small", resource_manager::constants::ENEMY_SMALL_TEX_ID),
+    ("medium", resource_manager::constants::ENEMY_MEDIUM_TEX_ID),
+    ("big", resource_manager::constants::ENEMY_BIG_TEX_ID),
+];
+
+pub fn enemy_kind(texture_id: &str) -> Option<&'static str> {
+    ENEMY_KINDS
+        .iter()
+        .find(|(_, texture)| *texture == texture_id)
+        .map(|(kind, _)| *kind)
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour on.
+pub fn format_duration(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Grade {
+    D,
+    C,
+    B,
+    A,
+    S,
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self {
+            Grade::S => "S",
+            Grade::A => "A",
+            Grade::B => "B",
+            Grade::C => "C",
+            Grade::D => "D",
+        };
+        write!(f, "{}", letter)
+    }
+}
+
+/// What happened during a single run.
+#[derive(Clone, Debug, Default)]
+pub struct RunStats {
+    /// Total of the team in a co-op game
+    pub score: u32,
+    /// Score of each player, in co-op they add up to `score`
+    pub player_scores: Vec<u32>,
+    pub shots: u32,
+    /// Shots that hit at least one enemy
+    pub hits: u32,
+    /// Kill counts by enemy kind
+    pub kills: HashMap<String, u32>,
+    pub time_survived: f32,
+    pub max_combo: u32,
+}
+
+impl RunStats {
+    pub fn record_kill(&mut self, texture_id: &str) {
+        if let Some(kind) = enemy_kind(texture_id) {
+            *self.kills.entry(kind.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    pub fn kills(&self, kind: &str) -> u32 {
+        self.kills.get(kind).copied().unwrap_or(0)
+    }
+
+    /// Share of shots that hit, 0.0 when nothing was fired.
+    pub fn accuracy(&self) -> f32 {
+        accuracy(self.hits, self.shots)
+    }
+
+    /// Rates the score, scaled up by accuracy and the best combo reached.
+    pub fn grade(&self) -> Grade {
+        let rating = self.score as f32
+            * (0.5 + self.accuracy())
+            * (1.0 + 0.05 * self.max_combo.saturating_sub(1) as f32);
+        match rating {
+            rating if rating >= 20000.0 => Grade::S,
+            rating if rating >= 10000.0 => Grade::A,
+            rating if rating >= 5000.0 => Grade::B,
+            rating if rating >= 2000.0 => Grade::C,
+            _ => Grade::D,
+        }
+    }
+}
+
+/// Totals over all runs, stored in `STATS_FILE` as `key=value` lines.
+#[derive(Clone, Debug, Default)]
+pub struct LifetimeStats {
+    pub runs: u32,
+    pub shots: u32,
+    pub hits: u32,
+    pub kills: HashMap<String, u32>,
+    pub time_played: f32,
+    pub total_score: u64,
+    pub best_combo: u32,
+}
+
+impl LifetimeStats {
+    pub fn load() -> Self {
+        let mut stats = LifetimeStats::default();
+        let Ok(content) = fs::read_to_string(STATS_FILE) else {
+            return stats;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "runs" => stats.runs = value.parse().unwrap_or(0),
+                "shots" => stats.shots = value.parse().unwrap_or(0),
+                "hits" => stats.hits = value.parse().unwrap_or(0),
+                "time_played" => stats.time_played = value.parse().unwrap_or(0.0),
+                "total_score" => stats.total_score = value.parse().unwrap_or(0),
+                "best_combo" => stats.best_combo = value.parse().unwrap_or(0),
+                _ => {
+                    if let Some(kind) = key.strip_prefix("kills.")
+                        && let Ok(count) = value.parse()
+                    {
+                        stats.kills.insert(kind.to_string(), count);
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn save(&self) {
+        let mut content = format!("runs={}\n", self.runs);
+        content.push_str(&format!("shots={}\n", self.shots));
+        content.push_str(&format!("hits={}\n", self.hits));
+        content.push_str(&format!("time_played={}\n", self.time_played));
+        content.push_str(&format!("total_score={}\n", self.total_score));
+        content.push_str(&format!("best_combo={}\n", self.best_combo));
+        let mut kills: Vec<_> = self.kills.iter().collect();
+        kills.sort();
+        for (kind, count) in kills {
+            content.push_str(&format!("kills.{}={}\n", kind, count));
+        }
+        fs::write(STATS_FILE, content).ok();
+    }
+
+    pub fn add_run(&mut self, run: &RunStats) {
+        self.runs += 1;
+        self.shots += run.shots;
+        self.hits += run.hits;
+        for (kind, count) in &run.kills {
+            *self.kills.entry(kind.clone()).or_insert(0) += count;
+        }
+        self.time_played += run.time_survived;
+        self.total_score += run.score as u64;
+        self.best_combo = self.best_combo.max(run.max_combo);
+    }
+
+    pub fn kills(&self, kind: &str) -> u32 {
+        self.kills.get(kind).copied().unwrap_or(0)
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        accuracy(self.hits, self.shots)
+    }
+}
+
+fn accuracy(hits: u32, shots: u32) -> f32 {
+    if shots == 0 {
+        0.0
+    } else {
+        (hits as f32 / shots as f32).min(1.0)
+    }
+}
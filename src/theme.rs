@@ -0,0 +1,216 @@
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, Style, StyleBuilder};
+use nanoserde::DeJson;
+use super::resource_manager::ResourceManager;
+
+pub const DEFAULT_THEME: &str = "default";
+
+/// Empty values in theme files are unset.
+#[derive(DeJson, Default)]
+struct StyleFile {
+    #[nserde(default)]
+    background: String,
+    #[nserde(default)]
+    background_hovered: String,
+    #[nserde(default)]
+    background_clicked: String,
+    #[nserde(default)]
+    background_margin: Vec<f32>,
+    #[nserde(default)]
+    margin: Vec<f32>,
+    #[nserde(default)]
+    font_size: f32,
+    #[nserde(default)]
+    color: String,
+    #[nserde(default)]
+    color_hovered: String,
+    #[nserde(default)]
+    color_clicked: String,
+    #[nserde(default)]
+    color_selected: String,
+    #[nserde(default)]
+    color_selected_hovered: String,
+    #[nserde(default)]
+    text_color: String,
+    #[nserde(default)]
+    text_color_hovered: String,
+    #[nserde(default)]
+    text_color_clicked: String,
+}
+
+#[derive(DeJson)]
+struct ThemeFile {
+    #[nserde(default)]
+    font: String,
+    #[nserde(default)]
+    font_scale: f32,
+    #[nserde(default)]
+    window: StyleFile,
+    #[nserde(default)]
+    button: StyleFile,
+    #[nserde(default)]
+    compact_button: StyleFile,
+    #[nserde(default)]
+    label: StyleFile,
+    #[nserde(default)]
+    compact_label: StyleFile,
+}
+
+/// Look of one kind of widget, unset fields keep macroquad's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct WidgetStyle {
+    /// Nine-slice background images by their file name
+    pub background: Option<String>,
+    pub background_hovered: Option<String>,
+    pub background_clicked: Option<String>,
+    /// Borders of the background that aren't stretched
+    pub background_margin: Option<RectOffset>,
+    pub margin: Option<RectOffset>,
+    /// Before the font's scale is applied
+    pub font_size: Option<f32>,
+    pub color: Option<Color>,
+    pub color_hovered: Option<Color>,
+    pub color_clicked: Option<Color>,
+    /// Background tint of the focused widget
+    pub color_selected: Option<Color>,
+    pub color_selected_hovered: Option<Color>,
+    pub text_color: Option<Color>,
+    pub text_color_hovered: Option<Color>,
+    pub text_color_clicked: Option<Color>,
+}
+
+impl WidgetStyle {
+    fn from_file(file: StyleFile) -> Result<Self, String> {
+        let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
+        let color = |value: String| non_empty(value).as_deref().map(parse_color).transpose();
+        let offset = |value: Vec<f32>| {
+            Some(value).filter(|value| !value.is_empty()).as_deref().map(parse_offset).transpose()
+        };
+        Ok(WidgetStyle {
+            background: non_empty(file.background),
+            background_hovered: non_empty(file.background_hovered),
+            background_clicked: non_empty(file.background_clicked),
+            background_margin: offset(file.background_margin)?,
+            margin: offset(file.margin)?,
+            font_size: Some(file.font_size).filter(|size| *size > 0.0),
+            color: color(file.color)?,
+            color_hovered: color(file.color_hovered)?,
+            color_clicked: color(file.color_clicked)?,
+            color_selected: color(file.color_selected)?,
+            color_selected_hovered: color(file.color_selected_hovered)?,
+            text_color: color(file.text_color)?,
+            text_color_hovered: color(file.text_color_hovered)?,
+            text_color_clicked: color(file.text_color_clicked)?,
+        })
+    }
+
+    /// Builds the macroquad style with the images loaded by `resource_manager`.
+    pub fn build(&self, resource_manager: &ResourceManager, font: &[u8], font_scale: f32) -> Style {
+        let image = |file: &str| resource_manager.get_image(file)
+            .unwrap_or_else(|| panic!("Theme image {} is not loaded.", file))
+            .clone();
+        let mut builder = root_ui()
+            .style_builder()
+            .font(font)
+            .unwrap();
+        if let Some(background) = &self.background {
+            builder = builder.background(image(background));
+        }
+        if let Some(background) = &self.background_hovered {
+            builder = builder.background_hovered(image(background));
+        }
+        if let Some(background) = &self.background_clicked {
+            builder = builder.background_clicked(image(background));
+        }
+        if let Some(margin) = self.background_margin {
+            builder = builder.background_margin(margin);
+        }
+        if let Some(margin) = self.margin {
+            builder = builder.margin(margin);
+        }
+        if let Some(font_size) = self.font_size {
+            builder = builder.font_size((font_size * font_scale) as u16);
+        }
+        for (color, set) in [
+            (self.color, StyleBuilder::color as fn(StyleBuilder, Color) -> StyleBuilder),
+            (self.color_hovered, StyleBuilder::color_hovered),
+            (self.color_clicked, StyleBuilder::color_clicked),
+            (self.color_selected, StyleBuilder::color_selected),
+            (self.color_selected_hovered, StyleBuilder::color_selected_hovered),
+            (self.text_color, StyleBuilder::text_color),
+            (self.text_color_hovered, StyleBuilder::text_color_hovered),
+            (self.text_color_clicked, StyleBuilder::text_color_clicked),
+        ] {
+            if let Some(color) = color {
+                builder = set(builder, color);
+            }
+        }
+        builder.build()
+    }
+
+    fn images(&self) -> impl Iterator<Item = &str> {
+        [&self.background, &self.background_hovered, &self.background_clicked]
+            .into_iter()
+            .filter_map(|image| image.as_deref())
+    }
+}
+
+/// Look of the UI windows and widgets, loaded from `themes/<id>.json`.
+pub struct Theme {
+    /// Font id used unless the language needs a font of its own
+    pub font: Option<String>,
+    /// Size multiplier for text in the theme's font
+    pub font_scale: f32,
+    pub window: WidgetStyle,
+    pub button: WidgetStyle,
+    /// Rows of settings menus
+    pub compact_button: WidgetStyle,
+    pub label: WidgetStyle,
+    pub compact_label: WidgetStyle,
+}
+
+impl Theme {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file = ThemeFile::deserialize_json(json).map_err(|err| err.to_string())?;
+        Ok(Theme {
+            font: Some(file.font).filter(|font| !font.is_empty()),
+            font_scale: if file.font_scale > 0.0 { file.font_scale } else { 1.0 },
+            window: WidgetStyle::from_file(file.window)?,
+            button: WidgetStyle::from_file(file.button)?,
+            compact_button: WidgetStyle::from_file(file.compact_button)?,
+            label: WidgetStyle::from_file(file.label)?,
+            compact_label: WidgetStyle::from_file(file.compact_label)?,
+        })
+    }
+
+    /// File names of all background images the theme refers to.
+    pub fn images(&self) -> Vec<&str> {
+        [&self.window, &self.button, &self.compact_button, &self.label, &self.compact_label]
+            .into_iter()
+            .flat_map(WidgetStyle::images)
+            .collect()
+    }
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa`.
+fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value.strip_prefix('#').ok_or_else(|| format!("Color {} doesn't start with #", value))?;
+    let channel = |index: usize| {
+        hex.get(index * 2..index * 2 + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            .ok_or_else(|| format!("Invalid color {}", value))
+    };
+    match hex.len() {
+        6 => Ok(Color::from_rgba(channel(0)?, channel(1)?, channel(2)?, 255)),
+        8 => Ok(Color::from_rgba(channel(0)?, channel(1)?, channel(2)?, channel(3)?)),
+        _ => Err(format!("Invalid color {}", value)),
+    }
+}
+
+/// Parses `[left, right, top, bottom]`.
+fn parse_offset(value: &[f32]) -> Result<RectOffset, String> {
+    match value {
+        [left, right, top, bottom] => Ok(RectOffset::new(*left, *right, *top, *bottom)),
+        _ => Err(format!("Expected [left, right, top, bottom], got {:?}", value)),
+    }
+}
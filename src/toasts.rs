@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+use super::settings::Settings;
+use super::typography::{self, Typography};
+use super::viewport::Anchor;
+
+const TOAST_SIZE: Vec2 = vec2(420.0, 76.0);
+const TOP_MARGIN: f32 = 30.0;
+const PADDING: f32 = 10.0;
+const SLIDE_SECONDS: f32 = 0.3;
+const SHOW_SECONDS: f32 = 3.0;
+const BACKGROUND_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.75);
+
+struct Toast {
+    heading: String,
+    text: String,
+    age: f32,
+}
+
+/// Notifications that slide in at the top of the screen one after another.
+///
+/// Drawn in screen space above everything else, so they stay up while scenes change.
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+    /// Slide in and out, otherwise toasts just appear and disappear
+    animated: bool,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Toasts {
+            queue: VecDeque::new(),
+            animated: true,
+        }
+    }
+
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.animated = !settings.reduced_motion;
+    }
+
+    pub fn show(&mut self, heading: &str, text: &str) {
+        self.queue.push_back(Toast {
+            heading: heading.to_string(),
+            text: text.to_string(),
+            age: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some(toast) = self.queue.front_mut() {
+            toast.age += delta_time;
+            if toast.age >= SHOW_SECONDS + SLIDE_SECONDS * 2.0 {
+                self.queue.pop_front();
+            }
+        }
+    }
+
+    pub fn draw(&self, typography: &Typography) {
+        let Some(toast) = self.queue.front() else {
+            return;
+        };
+        let shown = if self.animated {
+            let slide_out_start = SHOW_SECONDS + SLIDE_SECONDS;
+            (toast.age.min(slide_out_start + SLIDE_SECONDS - toast.age) / SLIDE_SECONDS).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let area = Rect::new(
+            (screen_width() - TOAST_SIZE.x) / 2.0,
+            -TOAST_SIZE.y + (TOAST_SIZE.y + TOP_MARGIN) * shown,
+            TOAST_SIZE.x,
+            TOAST_SIZE.y
+        );
+        draw_rectangle(area.x, area.y, area.w, area.h, BACKGROUND_COLOR);
+        draw_rectangle_lines(area.x, area.y, area.w, area.h, 2.0, GOLD);
+        let heading_area = typography.draw(
+            &toast.heading,
+            typography::HUD,
+            vec2(area.center().x, area.y + PADDING),
+            Anchor::TopCenter
+        );
+        typography.draw_wrapped(
+            &toast.text,
+            typography::POPUP,
+            vec2(area.center().x, heading_area.bottom() + PADDING),
+            Anchor::TopCenter,
+            area.w - PADDING * 2.0
+        );
+    }
+}
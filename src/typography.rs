@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use super::resource_manager::{self as resource_manager, ResourceManager};
+use super::viewport::Anchor;
+
+pub const TITLE: &str = "title";
+pub const HEADING: &str = "heading";
+pub const ALERT: &str = "alert";
+pub const BODY: &str = "body";
+pub const HUD: &str = "hud";
+pub const DEBUG: &str = "debug";
+pub const POPUP: &str = "popup";
+
+/// Distance between the baselines of wrapped lines relative to the line height
+const LINE_SPACING: f32 = 1.2;
+/// Cached measurements are dropped once there are this many, HUD text changes every frame
+const MAX_CACHED_MEASUREMENTS: usize = 512;
+
+#[derive(Clone, Debug)]
+pub struct TextStyle {
+    pub font: String,
+    pub size: u16,
+    pub color: Color,
+    /// Color and thickness in pixels
+    pub outline: Option<(Color, f32)>,
+    /// Color and offset
+    pub shadow: Option<(Color, Vec2)>,
+}
+
+impl TextStyle {
+    pub fn new(font: &str, size: u16, color: Color) -> Self {
+        TextStyle {
+            font: font.to_string(),
+            size,
+            color,
+            outline: None,
+            shadow: None,
+        }
+    }
+
+    pub fn with_outline(self, color: Color, thickness: f32) -> Self {
+        TextStyle {
+            outline: Some((color, thickness)),
+            ..self
+        }
+    }
+
+    pub fn with_shadow(self, color: Color, offset: Vec2) -> Self {
+        TextStyle {
+            shadow: Some((color, offset)),
+            ..self
+        }
+    }
+}
+
+/// Draws text in named styles, anchored and optionally wrapped to a width.
+///
+/// Styles refer to fonts by their `ResourceManager` id. While a language
+/// needs a fallback font it replaces the font of every style.
+pub struct Typography<'a> {
+    resource_manager: &'a ResourceManager,
+    styles: HashMap<String, TextStyle>,
+    fallback_font: Option<String>,
+    measurements: RefCell<HashMap<(String, u16, String), TextDimensions>>,
+}
+
+impl<'a> Typography<'a> {
+    pub fn new(resource_manager: &'a ResourceManager) -> Self {
+        let font = resource_manager::constants::FONT;
+        let mut typography = Typography {
+            resource_manager,
+            styles: HashMap::new(),
+            fallback_font: None,
+            measurements: RefCell::new(HashMap::new()),
+        };
+        typography.set_style(TITLE, TextStyle::new(font, 100, WHITE)
+            .with_shadow(Color::new(0.0, 0.0, 0.0, 0.6), vec2(4.0, 4.0)));
+        typography.set_style(HEADING, TextStyle::new(font, 50, WHITE)
+            .with_outline(BLACK, 2.0));
+        typography.set_style(ALERT, TextStyle::new(font, 50, RED)
+            .with_outline(BLACK, 2.0));
+        typography.set_style(BODY, TextStyle::new(font, 30, WHITE)
+            .with_outline(BLACK, 1.0));
+        typography.set_style(HUD, TextStyle::new(font, 25, WHITE)
+            .with_shadow(BLACK, vec2(2.0, 2.0)));
+        typography.set_style(DEBUG, TextStyle::new(font, 20, RED));
+        typography.set_style(POPUP, TextStyle::new(font, 30, GOLD)
+            .with_outline(BLACK, 1.0));
+        typography
+    }
+
+    pub fn set_style(&mut self, name: &str, style: TextStyle) {
+        self.styles.insert(name.to_string(), style);
+    }
+
+    pub fn set_fallback_font(&mut self, font: Option<&str>) {
+        self.fallback_font = font.map(str::to_string);
+    }
+
+    /// Splits `text` into lines no wider than `max_width`, breaking between
+    /// words and at newlines. A single word wider than `max_width` gets a line of its own.
+    pub fn wrap(&self, text: &str, style: &str, max_width: f32) -> Vec<String> {
+        let style = self.style(style);
+        let mut lines = vec![];
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if !line.is_empty() && self.measure_line(&candidate, style).width > max_width {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Draws `text` so that its `anchor` point is at `position`, lines are
+    /// aligned the same way horizontally. Returns the area the text covers.
+    pub fn draw(&self, text: &str, style: &str, position: Vec2, anchor: Anchor) -> Rect {
+        self.draw_wrapped(text, style, position, anchor, f32::INFINITY)
+    }
+
+    /// Draws like `draw` with all colors of the style made more transparent by `alpha`.
+    pub fn draw_faded(&self, text: &str, style: &str, position: Vec2, anchor: Anchor, alpha: f32) -> Rect {
+        self.draw_text_box(text, style, position, anchor, f32::INFINITY, alpha)
+    }
+
+    pub fn draw_wrapped(&self, text: &str, style: &str, position: Vec2, anchor: Anchor, max_width: f32) -> Rect {
+        self.draw_text_box(text, style, position, anchor, max_width, 1.0)
+    }
+
+    fn draw_text_box(
+        &self,
+        text: &str,
+        style_name: &str,
+        position: Vec2,
+        anchor: Anchor,
+        max_width: f32,
+        alpha: f32
+    ) -> Rect {
+        let style = self.style(style_name);
+        let lines = self.wrap(text, style_name, max_width);
+        let metrics = self.measure_line("Ay", style);
+        let line_height = metrics.height * LINE_SPACING;
+        let widths: Vec<f32> = lines.iter().map(|line| self.measure_line(line, style).width).collect();
+        let size = vec2(
+            widths.iter().copied().fold(0.0, f32::max),
+            metrics.height + line_height * (lines.len() - 1) as f32
+        );
+        let top_left = position - match anchor {
+            Anchor::TopLeft => vec2(0.0, 0.0),
+            Anchor::TopCenter => vec2(size.x / 2.0, 0.0),
+            Anchor::TopRight => vec2(size.x, 0.0),
+            Anchor::Center => size / 2.0,
+        };
+
+        for (index, (line, width)) in lines.iter().zip(widths).enumerate() {
+            let x = match anchor {
+                Anchor::TopLeft => top_left.x,
+                Anchor::TopCenter | Anchor::Center => top_left.x + (size.x - width) / 2.0,
+                Anchor::TopRight => top_left.x + size.x - width,
+            };
+            let baseline = top_left.y + metrics.offset_y + line_height * index as f32;
+            self.draw_line(line, style, vec2(x, baseline), alpha);
+        }
+        Rect::new(top_left.x, top_left.y, size.x, size.y)
+    }
+
+    fn draw_line(&self, text: &str, style: &TextStyle, position: Vec2, alpha: f32) {
+        let params = |color: Color| TextParams {
+            font: self.font(style),
+            font_size: style.size,
+            color: Color { a: color.a * alpha, ..color },
+            ..Default::default()
+        };
+        if let Some((color, offset)) = style.shadow {
+            draw_text_ex(text, position.x + offset.x, position.y + offset.y, params(color));
+        }
+        if let Some((color, thickness)) = style.outline {
+            for (dx, dy) in [(-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0), (1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)] {
+                draw_text_ex(text, position.x + dx * thickness, position.y + dy * thickness, params(color));
+            }
+        }
+        draw_text_ex(text, position.x, position.y, params(style.color));
+    }
+
+    fn measure_line(&self, text: &str, style: &TextStyle) -> TextDimensions {
+        let key = (self.font_id(style).to_string(), style.size, text.to_string());
+        let mut measurements = self.measurements.borrow_mut();
+        if let Some(dimensions) = measurements.get(&key) {
+            return *dimensions;
+        }
+        if measurements.len() >= MAX_CACHED_MEASUREMENTS {
+            measurements.clear();
+        }
+        let dimensions = measure_text(text, self.font(style), style.size, 1.0);
+        measurements.insert(key, dimensions);
+        dimensions
+    }
+
+    fn style(&self, name: &str) -> &TextStyle {
+        self.styles.get(name).unwrap_or_else(|| panic!("Unknown text style {}", name))
+    }
+
+    fn font_id<'s>(&'s self, style: &'s TextStyle) -> &'s str {
+        self.fallback_font.as_deref().unwrap_or(&style.font)
+    }
+
+    fn font(&self, style: &TextStyle) -> Option<&'a Font> {
+        self.resource_manager.get_text_font(self.font_id(style))
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::str::FromStr;
+use macroquad::prelude::*;
+
+pub const VIRTUAL_WIDTH: f32 = 800.0;
+pub const VIRTUAL_HEIGHT: f32 = 600.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// Keeps the aspect ratio and fills the rest of the window with bars
+    Letterbox,
+    /// Like `Letterbox`, but only scales by whole multiples for crisp pixels
+    IntegerScale,
+    /// Fills the window, distorting the playfield if the aspect ratio differs
+    Stretch,
+    /// Keeps the aspect ratio and shows more of the background along the longer
+    /// side, the playfield keeps its size so a seed plays the same in any window
+    Expand,
+}
+
+impl FromStr for ScalingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "letterbox" => Ok(ScalingMode::Letterbox),
+            "integer" => Ok(ScalingMode::IntegerScale),
+            "stretch" => Ok(ScalingMode::Stretch),
+            "expand" => Ok(ScalingMode::Expand),
+            _ => Err(format!("Unknown scaling mode: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ScalingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ScalingMode::Letterbox => "letterbox",
+            ScalingMode::IntegerScale => "integer",
+            ScalingMode::Stretch => "stretch",
+            ScalingMode::Expand => "expand",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ScalingMode {
+    pub const ALL: [ScalingMode; 4] = [
+        ScalingMode::Letterbox,
+        ScalingMode::IntegerScale,
+        ScalingMode::Stretch,
+        ScalingMode::Expand,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    Center,
+}
+
+/// Maps a fixed size virtual playfield onto the window.
+pub struct Viewport {
+    mode: ScalingMode,
+    virtual_size: Vec2,
+    /// Visible area in virtual units around the centered playfield, larger
+    /// than `virtual_size` only in `Expand` mode
+    size: Vec2,
+    /// Area of the window the playfield is rendered to, in logical pixels
+    screen_rect: Rect,
+    camera: Camera2D,
+    /// Set while the frame is rendered offscreen for post-processing
+    render_target: Option<RenderTarget>,
+}
+
+impl Viewport {
+    pub fn new(virtual_size: Vec2, mode: ScalingMode) -> Self {
+        let mut viewport = Viewport {
+            mode,
+            virtual_size,
+            size: virtual_size,
+            screen_rect: Rect::new(0.0, 0.0, virtual_size.x, virtual_size.y),
+            camera: Camera2D::default(),
+            render_target: None,
+        };
+        viewport.update();
+        viewport
+    }
+
+    /// Recomputes the mapping, call once per frame so window resizes are picked up.
+    pub fn update(&mut self) {
+        let screen = vec2(screen_width(), screen_height());
+        let fit = (screen.x / self.virtual_size.x).min(screen.y / self.virtual_size.y);
+        let (scale, size) = match self.mode {
+            ScalingMode::Letterbox => (vec2(fit, fit), self.virtual_size),
+            ScalingMode::IntegerScale => {
+                let scale = fit.floor().max(1.0);
+                (vec2(scale, scale), self.virtual_size)
+            }
+            ScalingMode::Stretch => (screen / self.virtual_size, self.virtual_size),
+            ScalingMode::Expand => (vec2(fit, fit), screen / fit),
+        };
+        let rendered = size * scale;
+        self.size = size;
+        self.screen_rect = Rect::new(
+            ((screen.x - rendered.x) / 2.0).round(),
+            ((screen.y - rendered.y) / 2.0).round(),
+            rendered.x.round(),
+            rendered.y.round()
+        );
+
+        // The viewport is given in physical pixels with the origin at the bottom of
+        // the screen, render targets are stored upside down so there it's the top
+        let dpi = screen_dpi_scale();
+        let viewport_y = match self.render_target {
+            Some(_) => self.screen_rect.y,
+            None => screen.y - self.screen_rect.bottom(),
+        };
+        self.camera = Camera2D {
+            target: self.virtual_size / 2.0,
+            zoom: vec2(2.0 / size.x, 2.0 / size.y),
+            viewport: Some((
+                (self.screen_rect.x * dpi) as i32,
+                (viewport_y * dpi) as i32,
+                (self.screen_rect.w * dpi) as i32,
+                (self.screen_rect.h * dpi) as i32,
+            )),
+            render_target: self.render_target.clone(),
+            ..Default::default()
+        };
+    }
+
+    pub fn set_mode(&mut self, mode: ScalingMode) {
+        self.mode = mode;
+        self.update();
+    }
+
+    pub fn set_render_target(&mut self, render_target: Option<RenderTarget>) {
+        self.render_target = render_target;
+        self.update();
+    }
+
+    pub fn camera(&self) -> &Camera2D {
+        &self.camera
+    }
+
+    /// Width of the playfield, the same in every scaling mode and window size.
+    pub fn width(&self) -> f32 {
+        self.virtual_size.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.virtual_size.y
+    }
+
+    /// Position of an anchor point on the playfield edges, pushed inwards by `margin`.
+    pub fn anchor(&self, anchor: Anchor, margin: Vec2) -> Vec2 {
+        let (w, h) = (self.virtual_size.x, self.virtual_size.y);
+        match anchor {
+            Anchor::TopLeft => vec2(margin.x, margin.y),
+            Anchor::TopCenter => vec2(w / 2.0, margin.y),
+            Anchor::TopRight => vec2(w - margin.x, margin.y),
+            Anchor::Center => vec2(w / 2.0, h / 2.0),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+#version 100
+
+precision mediump float;
+
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 iResolution;
+
+void main() {
+    vec3 col = texture2D(Texture, uv).rgb;
+    vec2 centered = uv - 0.5;
+    centered.x *= iResolution.x / iResolution.y;
+    col *= smoothstep(0.9, 0.35, length(centered));
+    gl_FragColor = vec4(col, 1.0);
+}
@@ -0,0 +1,221 @@
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use super::behaviour::Behaviour;
+use super::movement::{Formation, Movement};
+use super::resource_manager::constants;
+use super::rng::Rng;
+
+/// Fields a movement kind doesn't use are left out.
+#[derive(Clone, Debug, DeJson, Default)]
+struct MovementDefinition {
+    kind: String,
+    #[nserde(default)]
+    amplitude: f32,
+    #[nserde(default)]
+    frequency: f32,
+    #[nserde(default)]
+    period: f32,
+    #[nserde(default)]
+    curve: String,
+    #[nserde(default)]
+    points: Vec<Vec<f32>>,
+    #[nserde(default)]
+    duration: f32,
+    #[nserde(default)]
+    radius: f32,
+    #[nserde(default)]
+    angular_speed: f32,
+}
+
+impl MovementDefinition {
+    fn to_movement(&self) -> Result<Movement, String> {
+        let movement = match self.kind.as_str() {
+            "" | "straight" => Movement::Straight,
+            "sine" => Movement::Sine {
+                amplitude: self.amplitude,
+                frequency: self.frequency,
+            },
+            "zigzag" if self.period > 0.0 => Movement::Zigzag {
+                amplitude: self.amplitude,
+                period: self.period,
+            },
+            "path" if self.duration > 0.0 => {
+                let points = self.points
+                    .iter()
+                    .map(|point| match point.as_slice() {
+                        [x, y] => Ok(vec2(*x, *y)),
+                        _ => Err(format!("Path point needs x and y, got {:?}", point)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if points.len() < 2 {
+                    return Err("Path needs at least 2 points".to_string());
+                }
+                Movement::Path {
+                    curve: self.curve.parse()?,
+                    points,
+                    duration: self.duration,
+                }
+            }
+            "circle" => Movement::Circle {
+                radius: self.radius,
+                angular_speed: self.angular_speed,
+            },
+            "zigzag" => return Err("Zigzag needs a period".to_string()),
+            "path" => return Err("Path needs a duration".to_string()),
+            kind => return Err(format!("Unknown movement: {}", kind)),
+        };
+        Ok(movement)
+    }
+}
+
+/// Archetypes with a behaviour are steered by it instead of their movement.
+#[derive(DeJson)]
+struct ArchetypeDefinition {
+    id: String,
+    texture: String,
+    #[nserde(default)]
+    movement: MovementDefinition,
+    #[nserde(default)]
+    behaviour: String,
+    /// Archetype released by spawners
+    #[nserde(default)]
+    releases: String,
+}
+
+/// Empty values fall back to a random archetype, its own movement and no formation.
+#[derive(DeJson)]
+struct WaveDefinition {
+    weight: f32,
+    #[nserde(default)]
+    archetype: String,
+    #[nserde(default)]
+    count: u32,
+    #[nserde(default)]
+    formation: String,
+    #[nserde(default)]
+    movement: MovementDefinition,
+}
+
+#[derive(DeJson)]
+struct WavesFile {
+    archetypes: Vec<ArchetypeDefinition>,
+    waves: Vec<WaveDefinition>,
+}
+
+/// Kind of enemy, flying its movement unless a wave says otherwise or it
+/// has a behaviour of its own.
+#[derive(Clone, Debug)]
+pub struct EnemyArchetype {
+    pub id: String,
+    pub texture: String,
+    pub movement: Movement,
+    pub behaviour: Option<Behaviour>,
+    /// Index of the archetype a spawner releases
+    pub releases: Option<usize>,
+}
+
+/// Group of enemies spawned together.
+#[derive(Clone, Debug)]
+pub struct Wave {
+    /// Chance of the wave compared to the others
+    pub weight: f32,
+    /// Index into the archetypes, `None` picks one at random
+    pub archetype: Option<usize>,
+    pub count: u32,
+    pub formation: Option<Formation>,
+    /// Overrides the movement of the archetype
+    pub movement: Option<Movement>,
+}
+
+/// Enemy archetypes and the waves they spawn in.
+#[derive(Clone, Debug, Default)]
+pub struct WaveSet {
+    pub archetypes: Vec<EnemyArchetype>,
+    pub waves: Vec<Wave>,
+}
+
+impl WaveSet {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file = WavesFile::deserialize_json(json).map_err(|err| err.to_string())?;
+        let mut archetypes = vec![];
+        for archetype in &file.archetypes {
+            if !constants::ENEMY_TEXTURES.contains(&archetype.texture.as_str()) {
+                return Err(format!("Unknown enemy texture {}", archetype.texture));
+            }
+            let behaviour = if archetype.behaviour.is_empty() {
+                None
+            } else {
+                Some(archetype.behaviour.parse()?)
+            };
+            archetypes.push(EnemyArchetype {
+                id: archetype.id.clone(),
+                texture: archetype.texture.clone(),
+                movement: archetype.movement.to_movement()?,
+                behaviour,
+                releases: None,
+            });
+        }
+        if archetypes.is_empty() {
+            return Err("No enemy archetypes".to_string());
+        }
+        // Released archetypes are resolved once all of them are known
+        for (index, archetype) in file.archetypes.iter().enumerate() {
+            if archetypes[index].behaviour != Some(Behaviour::Spawner) {
+                continue;
+            }
+            let released = archetypes.iter().position(|other| other.id == archetype.releases);
+            archetypes[index].releases = Some(released.ok_or_else(|| {
+                format!("Spawner {} releases unknown archetype {}", archetype.id, archetype.releases)
+            })?);
+        }
+        let mut waves = vec![];
+        for wave in &file.waves {
+            let archetype = if wave.archetype.is_empty() {
+                None
+            } else {
+                let index = archetypes.iter().position(|archetype| archetype.id == wave.archetype);
+                Some(index.ok_or_else(|| format!("Unknown archetype {}", wave.archetype))?)
+            };
+            let formation = if wave.formation.is_empty() {
+                None
+            } else {
+                Some(wave.formation.parse()?)
+            };
+            waves.push(Wave {
+                weight: wave.weight,
+                archetype,
+                count: if formation.is_some() { wave.count.max(1) } else { 1 },
+                formation,
+                movement: if wave.movement.kind.is_empty() {
+                    None
+                } else {
+                    Some(wave.movement.to_movement()?)
+                },
+            });
+        }
+        if waves.iter().map(|wave| wave.weight).sum::<f32>() <= 0.0 {
+            return Err("No waves".to_string());
+        }
+        Ok(WaveSet { archetypes, waves })
+    }
+
+    /// Picks a wave by weight.
+    pub fn pick(&self, rng: &mut Rng) -> &Wave {
+        let total: f32 = self.waves.iter().map(|wave| wave.weight).sum();
+        let mut roll = rng.gen_range(0.0, total);
+        for wave in &self.waves {
+            if roll < wave.weight {
+                return wave;
+            }
+            roll -= wave.weight;
+        }
+        self.waves.last().unwrap()
+    }
+
+    /// Enemies a picked wave brings on average, spawn chances are divided
+    /// by it to keep the number of enemies independent of the waves.
+    pub fn average_count(&self) -> f32 {
+        let total: f32 = self.waves.iter().map(|wave| wave.weight).sum();
+        self.waves.iter().map(|wave| wave.weight * wave.count as f32).sum::<f32>() / total
+    }
+}
@@ -1,11 +1,37 @@
 use std::collections::HashMap;
 use macroquad::prelude::*;
-use macroquad::ui::{hash, root_ui, Skin, Ui, Id};
+use macroquad::ui::{hash, root_ui, Skin, Id};
+use super::dialog::{Dialog, DialogHandle, OpenDialog};
+use super::menu::{self, Layout, Menu, MenuState};
+use super::navigation::Navigation;
 use super::resource_manager::{self as resource_manager, ResourceManager};
+use super::theme::{self, WidgetStyle};
+
+/// Font size of macroquad's styles
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+const DIALOG_WIDTH: f32 = 440.0;
+const DIALOG_PADDING: f32 = 30.0;
+const DIALOG_LINE_HEIGHT: f32 = 34.0;
+const DIALOG_BUTTON_SPACING: f32 = 16.0;
+/// Offset of the title in the window header
+const DIALOG_TITLE_Y: f32 = -34.0;
 
 pub struct WindowManager<'a> {
     resource_manager: &'a ResourceManager,
     windows: HashMap<String, Id>,
+    menu_states: HashMap<String, MenuState>,
+    skin_pushed: bool,
+    /// Id of the theme skins are built from
+    theme: String,
+    /// Smaller text and buttons for the rows of settings menus
+    compact_skin: Option<Skin>,
+    /// Font id and size of labels, to center the text of dialogs
+    label_font: (String, u16),
+    /// Open dialogs, the last one is on top and the only one taking input
+    dialogs: Vec<OpenDialog>,
+    next_dialog_id: u64,
+    /// Choices picked in closed dialogs that weren't collected yet
+    dialog_results: HashMap<u64, usize>,
 }
 
 impl<'a> WindowManager<'a> {
@@ -13,65 +39,101 @@ impl<'a> WindowManager<'a> {
         WindowManager {
             resource_manager,
             windows: HashMap::new(),
+            menu_states: HashMap::new(),
+            skin_pushed: false,
+            theme: theme::DEFAULT_THEME.to_string(),
+            compact_skin: None,
+            label_font: (resource_manager::constants::FONT.to_string(), 0),
+            dialogs: vec![],
+            next_dialog_id: 0,
+            dialog_results: HashMap::new(),
+        }
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// Returns `false` and keeps the current theme if there is no theme with that id,
+    /// the skin has to be configured again to show it.
+    pub fn set_theme(&mut self, theme: &str) -> bool {
+        if self.resource_manager.get_theme(theme).is_none() {
+            warn!("Unknown theme {}", theme);
+            return false;
         }
+        self.theme = theme.to_string();
+        true
     }
 
-    pub fn configure_ui_skin(&self) {
-        let window_background = self.resource_manager
-            .get_image(resource_manager::constants::WINDOW_BACKGROUND).unwrap();
-        let button_background = self.resource_manager
-            .get_image(resource_manager::constants::BUTTON_BACKGROUND).unwrap();
-        let button_pressed_background = self.resource_manager
-            .get_image(resource_manager::constants::BUTTON_PRESSED_BACKGROUND).unwrap();
+    /// Replaces the UI skin with one built from the current theme. A font the
+    /// current language needs, with its `language_font_scale`, replaces the theme's font.
+    pub fn configure_ui_skin(&mut self, language_font: Option<&str>, language_font_scale: f32) {
+        let theme = self.resource_manager
+            .get_theme(&self.theme)
+            .expect("Theme is not loaded.");
+        let (font_id, font_scale) = match language_font {
+            Some(font) => (font, language_font_scale),
+            None => (
+                theme.font.as_deref().unwrap_or(resource_manager::constants::FONT),
+                theme.font_scale
+            ),
+        };
         let font = self.resource_manager
-            .get_font(resource_manager::constants::FONT).unwrap();
-
-        let window_style = root_ui()
-            .style_builder()
-            .background(window_background.clone())
-            .background_margin(RectOffset::new(32.0, 76.0, 44.0, 20.0))
-            .margin(RectOffset::new(0.0, -40.0, 0.0, 0.0))
-            .build();
-        let button_style = root_ui()
-            .style_builder()
-            .background(button_background.clone())
-            .background_clicked(button_pressed_background.clone())
-            .background_margin(RectOffset::new(16.0, 16.0, 16.0, 16.0))
-            .margin(RectOffset::new(16.0, 0.0, -8.0, -8.0))
-            .font(font)
-            .unwrap()
-            .text_color(WHITE)
-            .font_size(64)
-            .build();
-        let label_style = root_ui()
-            .style_builder()
-            .font(font)
-            .unwrap()
-            .text_color(WHITE)
-            .font_size(28)
-            .build();
+            .get_font(font_id).unwrap();
+
+        let style = |style: &WidgetStyle| style.build(self.resource_manager, font, font_scale);
+        let window_style = style(&theme.window);
+        let button_style = style(&theme.button);
+        let compact_button_style = style(&theme.compact_button);
+        let label_style = style(&theme.label);
+        let compact_label_style = style(&theme.compact_label);
+        self.label_font = (
+            font_id.to_string(),
+            (theme.label.font_size.unwrap_or(DEFAULT_FONT_SIZE) * font_scale) as u16
+        );
         let ui_skin = Skin {
             window_style,
             button_style,
             label_style,
             ..root_ui().default_skin()
         };
+        self.compact_skin = Some(Skin {
+            button_style: compact_button_style,
+            label_style: compact_label_style,
+            ..ui_skin.clone()
+        });
+        if self.skin_pushed {
+            root_ui().pop_skin();
+        }
         root_ui().push_skin(&ui_skin);
+        self.skin_pushed = true;
     }
 
-    pub fn window<F: FnOnce(&mut Ui)>(
+    /// Window with a `Menu` whose widgets are laid out in `layout`, it's
+    /// disabled while a dialog is open.
+    pub fn menu<F: FnOnce(&mut Menu)>(
         &mut self,
         name: &str,
-        positon: Vec2,
+        position: Vec2,
         size: Vec2,
+        layout: Layout,
+        navigation: &Navigation,
         f: F
     ) -> bool {
-        let id = hash!();
+        let id = hash!(name);
+        let compact_skin = self.compact_skin.as_ref().expect("UI skin is not configured.");
+        let state = self.menu_states.entry(name.to_string()).or_default();
+        let enabled = self.dialogs.is_empty();
         let result = root_ui().window(
             id,
-            positon,
+            position,
             size,
-            f
+            |ui| {
+                let mut menu = Menu::new(ui, compact_skin, navigation, state, layout);
+                menu.set_enabled(enabled);
+                f(&mut menu);
+                menu.finish();
+            }
         );
         if result {
             self.windows.insert(name.to_string(), id);
@@ -79,6 +141,93 @@ impl<'a> WindowManager<'a> {
         result
     }
 
+    /// Puts a dialog on top of the stack, it shows up from the next `update_dialogs` on.
+    pub fn open_dialog<T>(&mut self, dialog: Dialog<T>) -> DialogHandle<T> {
+        let (dialog, handle) = dialog.open(self.next_dialog_id);
+        self.next_dialog_id += 1;
+        self.dialogs.push(dialog);
+        handle
+    }
+
+    /// The value picked in the dialog, once it has been closed.
+    pub fn dialog_result<T: Clone>(&mut self, handle: &DialogHandle<T>) -> Option<T> {
+        let choice = self.dialog_results.remove(&handle.id())?;
+        handle.value(choice)
+    }
+
+    /// Takes the dialog down without an answer, e.g. when the question doesn't apply anymore.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn close_dialog<T: Clone>(&mut self, handle: &DialogHandle<T>) {
+        self.dialogs.retain(|dialog| dialog.id != handle.id());
+        self.dialog_results.remove(&handle.id());
+    }
+
+    /// Draws the open dialogs above all other windows and lets the top one
+    /// handle input. Call before the scenes are updated, while a dialog is
+    /// open `navigation` is cleared so they don't react to it.
+    pub fn update_dialogs(&mut self, navigation: &mut Navigation) {
+        let Some(top) = self.dialogs.len().checked_sub(1) else {
+            return;
+        };
+        let compact_skin = self.compact_skin.as_ref().expect("UI skin is not configured.");
+        let (font_id, font_size) = &self.label_font;
+        let font = self.resource_manager.get_text_font(font_id);
+        let centered_x = |text: &str| {
+            (DIALOG_WIDTH - measure_text(text, font, *font_size, 1.0).width) / 2.0
+        };
+
+        let mut answer = None;
+        for (index, dialog) in self.dialogs.iter().enumerate() {
+            let name = dialog.window_name();
+            let id = hash!(&name);
+            let lines: Vec<&str> = dialog.message.split('\n').collect();
+            let buttons_top = DIALOG_PADDING + lines.len() as f32 * DIALOG_LINE_HEIGHT + DIALOG_PADDING;
+            let buttons_height = dialog.labels.len() as f32 * (menu::BUTTON_HEIGHT + DIALOG_BUTTON_SPACING);
+            let size = vec2(DIALOG_WIDTH, buttons_top + buttons_height + DIALOG_PADDING);
+            let position = vec2(screen_width() - size.x, screen_height() - size.y) / 2.0;
+            let layout = Layout::vertical(
+                Rect::new(DIALOG_PADDING * 2.0, buttons_top, size.x - DIALOG_PADDING * 4.0, buttons_height),
+                DIALOG_BUTTON_SPACING
+            );
+            let state = self.menu_states.entry(name).or_default();
+            let active = index == top;
+            root_ui().window(
+                id,
+                position,
+                size,
+                |ui| {
+                    let mut menu = Menu::new(ui, compact_skin, navigation, state, layout);
+                    menu.set_enabled(active);
+                    menu.ui().label(vec2(centered_x(&dialog.title), DIALOG_TITLE_Y), &dialog.title);
+                    for (line_index, line) in lines.iter().enumerate() {
+                        let y = DIALOG_PADDING + line_index as f32 * DIALOG_LINE_HEIGHT;
+                        menu.ui().label(vec2(centered_x(line), y), line);
+                    }
+                    for (choice, label) in dialog.labels.iter().enumerate() {
+                        if menu.button(label) {
+                            answer = Some(choice);
+                        }
+                    }
+                    if menu.back() {
+                        answer = Some(dialog.labels.len().saturating_sub(1));
+                    }
+                    menu.finish();
+                }
+            );
+            // Windows clicked below would otherwise be raised over the dialog
+            root_ui().move_window(id, position);
+            root_ui().focus_window(id);
+        }
+        navigation.clear();
+
+        if let Some(choice) = answer
+            && let Some(dialog) = self.dialogs.pop()
+        {
+            self.menu_states.remove(&dialog.window_name());
+            self.dialog_results.insert(dialog.id, choice);
+        }
+    }
+
     pub fn move_window(&self, id: Id, position: Vec2) {
         root_ui().move_window(id, position);
     }